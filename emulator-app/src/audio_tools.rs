@@ -0,0 +1,77 @@
+use emulator_common::{Resampler, SampleBuffer, WavSink};
+use serde_json::Value;
+
+// Records everything the emulator plays into a WAV file, set up from the "audio_tools" block of a platform.
+pub struct AudioTools
+{
+	wav_path: Option<String>,
+	// 0 keeps the platform's own rate.
+	sample_rate: u32,
+	sink: Option<(WavSink, Resampler)>
+}
+
+impl AudioTools
+{
+	pub fn from_json(data: &Value) -> Self
+	{
+		Self {
+			wav_path: data["wav_path"].as_str().map(|path| path.to_string()),
+			sample_rate: data["sample_rate"].as_u64().unwrap_or(0) as u32,
+			sink: None
+		}
+	}
+
+	pub fn record(&mut self, samples: &SampleBuffer)
+	{
+		let path = match &self.wav_path
+		{
+			Some(path) => path.clone(),
+			None => return,
+		};
+
+		// The file is only opened once the platform's rate is known.
+		if self.sink.is_none()
+		{
+			let output_rate = match self.sample_rate
+			{
+				0 => samples.get_rate(),
+				rate => rate
+			};
+
+			match WavSink::create(&path, output_rate)
+			{
+				Ok(sink) => self.sink = Some((sink, Resampler::new(samples.get_rate(), output_rate))),
+				Err(error) =>
+				{
+					error!("Could not create {}: {}", path, error);
+					self.wav_path = None;
+					return;
+				},
+			}
+		}
+
+		if let Some((sink, resampler)) = &mut self.sink
+		{
+			let resampled = resampler.process_buffer(samples);
+
+			if let Err(error) = sink.write(resampled.get_samples())
+			{
+				error!("Could not write audio to {}: {}", path, error);
+			}
+		}
+	}
+
+	pub fn finish(&mut self)
+	{
+		if let Some((sink, _)) = self.sink.take()
+		{
+			let sample_count = sink.get_sample_count();
+
+			match sink.finish()
+			{
+				Ok(()) => info!("Recorded {} audio samples.", sample_count),
+				Err(error) => error!("Could not finish the audio recording: {}", error),
+			}
+		}
+	}
+}
@@ -0,0 +1,349 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use emulator_chip8 as CHIP8;
+use emulator_common::fnv1a_64;
+use serde_json::{json, Value};
+
+// Everything found out about a single ROM during a headless run.
+struct RomReport
+{
+	path: String,
+	frames: u64,
+	instructions: u64,
+	unknown_opcode: Option<(u16, u16)>,
+	fault: Option<String>,
+	halted_at: Option<u64>,
+	quirks: BTreeMap<&'static str, u64>,
+	screen_hash: u64,
+	screen_text: String,
+	thumbnail: String
+}
+
+impl RomReport
+{
+	fn status(&self) -> String
+	{
+		if let Some(fault) = &self.fault
+		{
+			return format!("fault: {}", fault);
+		}
+
+		if let Some((pc, opcode)) = self.unknown_opcode
+		{
+			return format!("unknown opcode {:04X} at {:#05X}", opcode, pc);
+		}
+
+		if let Some(frame) = self.halted_at
+		{
+			return format!("halted at frame {}", frame);
+		}
+
+		return "ok".to_string();
+	}
+
+	fn quirk_summary(&self) -> String
+	{
+		return self
+			.quirks
+			.iter()
+			.map(|(quirk, count)| format!("{}={}", quirk, count))
+			.collect::<Vec<String>>()
+			.join(" ");
+	}
+
+	fn to_json(&self) -> Value
+	{
+		return json!({
+			"rom": self.path,
+			"status": self.status(),
+			"frames": self.frames,
+			"instructions": self.instructions,
+			"unknown_opcode": self.unknown_opcode.map(|(pc, opcode)| json!({ "pc": pc, "opcode": opcode })),
+			"fault": self.fault,
+			"halted_at": self.halted_at,
+			"quirks": self.quirks,
+			"screen_hash": format!("{:016X}", self.screen_hash),
+			"screen_text": self.screen_text,
+			"thumbnail": self.thumbnail
+		});
+	}
+}
+
+fn collect_roms(directory: &Path, extensions: &[String], output: &mut Vec<PathBuf>)
+{
+	let entries = match std::fs::read_dir(directory)
+	{
+		Ok(entries) => entries,
+		Err(error) =>
+		{
+			error!("Could not read directory {}: {}", directory.display(), error);
+			return;
+		},
+	};
+
+	for entry in entries.flatten()
+	{
+		let path = entry.path();
+
+		if path.is_dir()
+		{
+			collect_roms(&path, extensions, output);
+			continue;
+		}
+
+		let extension = path
+			.extension()
+			.and_then(|extension| extension.to_str())
+			.unwrap_or("")
+			.to_lowercase();
+
+		if extensions.contains(&extension)
+		{
+			output.push(path);
+		}
+	}
+}
+
+fn run_rom(
+	path: &Path,
+	backend_config: &Value,
+	frames: u64,
+	glyphs: &CHIP8::GlyphTable
+) -> RomReport
+{
+	let mut report = RomReport {
+		path: path.display().to_string(),
+		frames: 0,
+		instructions: 0,
+		unknown_opcode: None,
+		fault: None,
+		halted_at: None,
+		quirks: BTreeMap::new(),
+		screen_hash: 0,
+		screen_text: String::new(),
+		thumbnail: String::new()
+	};
+
+	let config = CHIP8::Configs::EmulatorConfig::from_json(backend_config);
+	let mut emulator = CHIP8::Emulator::new(&config);
+	emulator.seed_random(0);
+
+	if let Err(error) = emulator.try_load(&report.path)
+	{
+		report.fault = Some(error);
+		return report;
+	}
+
+	'frames: for frame in 0 .. frames
+	{
+		for _ in 0 .. emulator.get_instructions_per_frame()
+		{
+			let pc = emulator.get_pc();
+			let opcode = emulator.peek_opcode();
+			emulator.step();
+
+			match emulator.get_fault()
+			{
+				Some(CHIP8::Components::Fault::UnknownOpcode { pc, opcode }) =>
+				{
+					report.unknown_opcode = Some((pc, opcode));
+					break 'frames;
+				},
+				Some(fault) =>
+				{
+					report.fault = Some(fault.to_string());
+					break 'frames;
+				},
+				None => report.instructions += 1,
+			}
+
+			// Instructions that wait (display wait, key wait) rewind the PC, count them once they run.
+			if emulator.get_pc() != pc
+			{
+				for quirk in CHIP8::sensitive_quirks(opcode)
+				{
+					*report.quirks.entry(quirk).or_insert(0) += 1;
+				}
+			}
+
+			if !emulator.is_running()
+			{
+				report.halted_at = Some(frame);
+				break 'frames;
+			}
+		}
+
+		emulator.tick_timers();
+		report.frames = frame + 1;
+	}
+
+	report.screen_hash = fnv1a_64(emulator.get_display_data());
+	report.screen_text = emulator.read_screen_text(glyphs);
+	report.thumbnail = emulator
+		.get_display_image(0xFFFFFF, 0x000000)
+		.scaled(2)
+		.to_png_data_uri();
+
+	return report;
+}
+
+fn escape_html(text: &str) -> String
+{
+	return text
+		.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;");
+}
+
+fn build_html(reports: &[RomReport]) -> String
+{
+	let mut result = String::from(
+		"<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>remu CHIP8 compatibility report</title>\n<style>\nbody { font-family: sans-serif; }\ntd, th { padding: 4px 8px; border-bottom: 1px solid #ccc; text-align: left; }\nimg { image-rendering: pixelated; width: 256px; }\n</style>\n</head>\n<body>\n<table>\n<tr><th>ROM</th><th>Status</th><th>Frames</th><th>Quirk-sensitive instructions</th><th>Screen hash</th><th>Screen text</th><th>Final frame</th></tr>\n"
+	);
+
+	for report in reports
+	{
+		result.push_str(&format!(
+			"<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td><code>{:016X}</code></td><td><pre>{}</pre></td><td><img src=\"{}\"></td></tr>\n",
+			escape_html(&report.path),
+			escape_html(&report.status()),
+			report.frames,
+			escape_html(&report.quirk_summary()),
+			report.screen_hash,
+			escape_html(&report.screen_text),
+			report.thumbnail
+		));
+	}
+
+	result.push_str("</table>\n</body>\n</html>\n");
+
+	return result;
+}
+
+// Runs every ROM in a directory headlessly and reports how each one behaved.
+pub fn run_batch(config: &Value) -> i32
+{
+	let platform = &config["platform"];
+	let settings = &config["batch"];
+
+	if platform["name"].as_str().unwrap_or("none").to_uppercase() != "CHIP8"
+	{
+		error!("Batch mode only supports the CHIP8 platform!");
+		println!("Batch mode only supports the CHIP8 platform!");
+		return 2;
+	}
+
+	let directory = settings["directory"].as_str().unwrap_or("roms");
+	let frames = settings["frames"].as_u64().unwrap_or(600);
+	let extensions: Vec<String> = match settings["extensions"].as_array()
+	{
+		Some(extensions) => extensions
+			.iter()
+			.filter_map(|extension| extension.as_str())
+			.map(|extension| extension.to_lowercase())
+			.collect(),
+		None => vec!["ch8".to_string()]
+	};
+
+	let glyphs = CHIP8::GlyphTable::from_json(&settings["glyphs"]);
+
+	let mut roms = Vec::new();
+	collect_roms(Path::new(directory), &extensions, &mut roms);
+	roms.sort();
+
+	let threads = match settings["threads"].as_u64().unwrap_or(0)
+	{
+		0 => std::thread::available_parallelism()
+			.map(|count| count.get())
+			.unwrap_or(1),
+		count => count as usize
+	};
+
+	println!(
+		"Running {} ROMs from {} for {} frames on {} threads...",
+		roms.len(),
+		directory,
+		frames,
+		threads
+	);
+
+	let next = AtomicUsize::new(0);
+	let results = Mutex::new(Vec::new());
+
+	std::thread::scope(|scope| {
+		for _ in 0 .. threads.min(roms.len())
+		{
+			scope.spawn(|| loop
+			{
+				let index = next.fetch_add(1, Ordering::Relaxed);
+				if index >= roms.len()
+				{
+					break;
+				}
+
+				let report = run_rom(
+					&roms[index],
+					&platform["backend_config"],
+					frames,
+					&glyphs
+				);
+				results.lock().unwrap().push((index, report));
+			});
+		}
+	});
+
+	let mut results = results.into_inner().unwrap();
+	results.sort_by_key(|(index, _)| *index);
+	let reports: Vec<RomReport> = results.into_iter().map(|(_, report)| report).collect();
+
+	// Plain table for the terminal.
+	let name_width = reports
+		.iter()
+		.map(|report| report.path.len())
+		.max()
+		.unwrap_or(3)
+		.max(3);
+	println!(
+		"{:name_width$}  {:16}  {:40}  QUIRKS",
+		"ROM",
+		"SCREEN HASH",
+		"STATUS",
+		name_width = name_width
+	);
+	for report in &reports
+	{
+		println!(
+			"{:name_width$}  {:016X}  {:40}  {}",
+			report.path,
+			report.screen_hash,
+			report.status(),
+			report.quirk_summary(),
+			name_width = name_width
+		);
+	}
+
+	if let Some(path) = settings["json_output"].as_str()
+	{
+		let data = Value::Array(reports.iter().map(|report| report.to_json()).collect());
+		match std::fs::write(path, serde_json::to_string_pretty(&data).unwrap())
+		{
+			Ok(_) => println!("JSON report written to {}.", path),
+			Err(error) => error!("Could not write JSON report to {}: {}", path, error)
+		}
+	}
+
+	if let Some(path) = settings["html_output"].as_str()
+	{
+		match std::fs::write(path, build_html(&reports))
+		{
+			Ok(_) => println!("HTML report written to {}.", path),
+			Err(error) => error!("Could not write HTML report to {}: {}", path, error)
+		}
+	}
+
+	return 0;
+}
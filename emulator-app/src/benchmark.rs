@@ -0,0 +1,65 @@
+use std::time::Instant;
+
+use serde_json::Value;
+
+use crate::platforms::create_system;
+use crate::test_runner::{EXIT_ERROR, EXIT_PASS};
+
+// Every supported platform runs at 60 frames per second, speed is reported as a multiple of that.
+const NATIVE_FRAME_RATE: f64 = 60.0;
+
+// Runs a ROM headlessly as fast as possible and reports how many frames per second the core manages.
+pub fn run_benchmark(config: &Value) -> i32
+{
+	let platform = &config["platform"];
+	let settings = &config["benchmark"];
+
+	let name = platform["name"].as_str().unwrap_or("none");
+	let rom = platform["rom"].as_str().unwrap_or("none");
+	let frames = settings["frames"].as_u64().unwrap_or(600).max(1);
+	let warmup = settings["warmup"].as_u64().unwrap_or(60);
+
+	let mut emulator = match create_system(name, &platform["backend_config"])
+	{
+		Ok(emulator) => emulator,
+		Err(error) =>
+		{
+			println!("ERROR   {}", error);
+			return EXIT_ERROR;
+		},
+	};
+
+	if let Err(error) = emulator.try_load(rom)
+	{
+		println!("ERROR   {}", error);
+		return EXIT_ERROR;
+	}
+
+	// Let caches and the allocator settle before measuring.
+	for _ in 0 .. warmup
+	{
+		emulator.run_frame();
+	}
+
+	let start = Instant::now();
+	for _ in 0 .. frames
+	{
+		emulator.run_frame();
+	}
+	let seconds = start.elapsed().as_secs_f64().max(f64::EPSILON);
+
+	let frames_per_second = frames as f64 / seconds;
+
+	println!("rom       {}", rom);
+	println!("frames    {}", frames);
+	println!("seconds   {:.3}", seconds);
+	println!("fps       {:.1}", frames_per_second);
+	println!("speed     {:.1}x real time", frames_per_second / NATIVE_FRAME_RATE);
+
+	if let Some(fault) = emulator.get_fault()
+	{
+		println!("warning   the program faulted: {}", fault);
+	}
+
+	return EXIT_PASS;
+}
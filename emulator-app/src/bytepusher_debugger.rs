@@ -0,0 +1,245 @@
+use std::io::BufRead;
+
+use emulator_bytepusher as BYTEPUSHER;
+use serde_json::Value;
+
+use crate::test_runner::{EXIT_ERROR, EXIT_PASS};
+
+const HELP: &str = "\
+step [count]         run count instructions, 1 by default
+frame                run to the end of the frame
+continue [frames]    run until a breakpoint or watch stops it, at most 600 frames by default
+break ADDRESS        set or remove a breakpoint
+watch START [END]    stop on writes from START to END
+unwatch              remove every watch
+frames on|off        stop at every frame boundary
+hex [ADDRESS]        hex view of the page holding ADDRESS, the next page without one
+state                pc, frame and the header fields
+press KEY            hold a key down
+release KEY          let a key go
+quit";
+
+// A console debugger for BytePusher, reading one command per line from stdin.
+pub fn run_debugger(config: &Value) -> i32
+{
+	let platform = &config["platform"];
+	let settings = &config["debugger"];
+	let rom = platform["rom"].as_str().unwrap_or("none");
+
+	let backend_config = BYTEPUSHER::Configs::EmulatorConfig::from_json(&platform["backend_config"]);
+	let mut emulator = BYTEPUSHER::Emulator::new(&backend_config);
+
+	if let Err(error) = emulator.try_load(rom)
+	{
+		println!("ERROR   {}", error);
+		return EXIT_ERROR;
+	}
+
+	let mut debugger = BYTEPUSHER::Debugger::new();
+	debugger.set_break_on_frame(settings["break_on_frame"].as_bool().unwrap_or(false));
+
+	for address in settings["breakpoints"].as_array().unwrap_or(&Vec::new())
+	{
+		if let Some(address) = value_to_address(address)
+		{
+			debugger.toggle_breakpoint(address);
+		}
+	}
+
+	for range in settings["watches"].as_array().unwrap_or(&Vec::new())
+	{
+		let start = value_to_address(&range[0]);
+		let end = value_to_address(&range[1]).or(start);
+
+		if let (Some(start), Some(end)) = (start, end)
+		{
+			debugger.add_watch(start, end);
+		}
+	}
+
+	println!("Loaded {}, type help for the commands.", rom);
+	print_location(&emulator);
+
+	let mut hex_page = 0;
+
+	for line in std::io::stdin().lock().lines().map_while(Result::ok)
+	{
+		let words: Vec<&str> = line.split_whitespace().collect();
+		let argument = |index: usize| -> Option<u32> { return words.get(index).and_then(|word| parse_address(word)); };
+
+		match words.first().copied().unwrap_or("")
+		{
+			"" =>
+			{},
+
+			"step" | "s" =>
+			{
+				let count = argument(1).unwrap_or(1) as usize;
+				let reason = debugger.run(&mut emulator, count);
+				print_stop(&emulator, reason);
+			},
+
+			"frame" | "f" =>
+			{
+				let reason = debugger.run_to_frame_end(&mut emulator);
+				print_stop(&emulator, reason);
+			},
+
+			"continue" | "c" =>
+			{
+				let frames = argument(1).unwrap_or(600) as usize;
+				let reason = debugger.run(&mut emulator, frames * BYTEPUSHER::Components::INSTRUCTIONS_PER_FRAME);
+				print_stop(&emulator, reason);
+			},
+
+			"break" | "b" => match argument(1)
+			{
+				Some(address) =>
+				{
+					let state = if debugger.toggle_breakpoint(address) { "set" } else { "removed" };
+					println!("Breakpoint at {:#08X} {}.", address, state);
+				},
+				None => println!("Usage: break ADDRESS"),
+			},
+
+			"watch" | "w" => match argument(1)
+			{
+				Some(start) =>
+				{
+					let end = argument(2).unwrap_or(start);
+					debugger.add_watch(start, end);
+					println!("Watching writes from {:#08X} to {:#08X}.", start.min(end), start.max(end));
+				},
+				None => println!("Usage: watch START [END]"),
+			},
+
+			"unwatch" =>
+			{
+				debugger.clear_watches();
+				println!("Removed every watch.");
+			},
+
+			"frames" =>
+			{
+				debugger.set_break_on_frame(words.get(1) != Some(&"off"));
+				println!("Frame boundaries {}.", if debugger.get_break_on_frame() { "stop" } else { "run on" });
+			},
+
+			"hex" | "x" =>
+			{
+				hex_page = match argument(1)
+				{
+					Some(address) => address as usize / BYTEPUSHER::HEX_PAGE_SIZE,
+					None => hex_page
+				};
+
+				print!("{}", BYTEPUSHER::hex_page(&emulator, hex_page));
+				hex_page += 1;
+			},
+
+			"state" | "r" => print_state(&emulator, &debugger),
+
+			"press" | "release" => match argument(1).filter(|key| *key < 16)
+			{
+				Some(key) if words[0] == "press" => emulator.press_key(key as u8),
+				Some(key) => emulator.release_key(key as u8),
+				None => println!("Keys go from 0 to 0xF."),
+			},
+
+			"help" | "h" => println!("{}", HELP),
+
+			"quit" | "q" => break,
+
+			command => println!("Unknown command {}, type help for the commands.", command),
+		}
+	}
+
+	return EXIT_PASS;
+}
+
+// Decimal, or hexadecimal with a 0x prefix, wrapped to 24 bits.
+fn parse_address(text: &str) -> Option<u32>
+{
+	let value = match text.strip_prefix("0x").or(text.strip_prefix("0X"))
+	{
+		Some(hex) => u32::from_str_radix(hex, 16).ok(),
+		None => text.parse::<u32>().ok()
+	};
+
+	return value.map(|value| value & BYTEPUSHER::Components::ADDRESS_MASK as u32);
+}
+
+// Addresses in the config can be numbers or strings like "0x010000", both wrapped to 24 bits.
+fn value_to_address(value: &Value) -> Option<u32>
+{
+	return match value
+	{
+		Value::Number(number) => number
+			.as_u64()
+			.map(|number| (number & BYTEPUSHER::Components::ADDRESS_MASK as u64) as u32),
+		Value::String(text) => parse_address(text),
+		_ => None
+	};
+}
+
+fn print_location(emulator: &BYTEPUSHER::Emulator)
+{
+	let pc = emulator.get_pc() as usize;
+	let instruction = BYTEPUSHER::Instruction::read(emulator.get_memory(), pc);
+
+	println!(
+		"frame {} instruction {:5}  next {:06X}  {}",
+		emulator.get_snapshot().get_frame_count(),
+		emulator.get_frame_position(),
+		pc,
+		instruction.to_source()
+	);
+}
+
+fn print_stop(emulator: &BYTEPUSHER::Emulator, reason: BYTEPUSHER::StopReason)
+{
+	match reason
+	{
+		BYTEPUSHER::StopReason::Stepped =>
+		{},
+
+		BYTEPUSHER::StopReason::FrameEnd => println!("End of frame."),
+
+		BYTEPUSHER::StopReason::Breakpoint(address) => println!("Breakpoint at {:#08X}.", address),
+
+		BYTEPUSHER::StopReason::Watchpoint(write) => println!(
+			"Watch: {:06X} wrote {:#04X} over {:#04X} at {:#08X}, copied from {:#08X}.",
+			write.pc,
+			write.new_value,
+			write.old_value,
+			write.destination,
+			write.source
+		),
+	}
+
+	print_location(emulator);
+}
+
+fn print_state(emulator: &BYTEPUSHER::Emulator, debugger: &BYTEPUSHER::Debugger)
+{
+	print_location(emulator);
+
+	for region in BYTEPUSHER::memory_regions(emulator)
+	{
+		println!("{:<14}{:06X} .. {:06X}", region.name, region.start, region.end);
+	}
+
+	let breakpoints: Vec<String> = debugger
+		.get_breakpoints()
+		.iter()
+		.map(|address| format!("{:06X}", address))
+		.collect();
+	println!("breakpoints   {}", breakpoints.join(" "));
+
+	let watches: Vec<String> = debugger
+		.get_watches()
+		.iter()
+		.map(|(start, end)| format!("{:06X}-{:06X}", start, end))
+		.collect();
+	println!("watches       {}", watches.join(" "));
+}
@@ -0,0 +1,78 @@
+use emulator_bytepusher as BYTEPUSHER;
+use serde_json::Value;
+
+use crate::test_runner::{EXIT_ERROR, EXIT_PASS};
+
+// Turns the "source" file of the "assembler" block into the "output" .BytePusher image.
+pub fn run_assemble(config: &Value) -> i32
+{
+	let settings = &config["assembler"];
+	let source_path = settings["source"].as_str().unwrap_or("program.bpa");
+	let output_path = settings["output"].as_str().unwrap_or("program.BytePusher");
+
+	let source = match std::fs::read_to_string(source_path)
+	{
+		Ok(source) => source,
+		Err(error) =>
+		{
+			println!("ERROR   could not read {}: {}", source_path, error);
+			return EXIT_ERROR;
+		},
+	};
+
+	let image = match BYTEPUSHER::assemble(&source)
+	{
+		Ok(image) => image,
+		Err(error) =>
+		{
+			println!("ERROR   {}: {}", source_path, error);
+			return EXIT_ERROR;
+		},
+	};
+
+	if let Err(error) = std::fs::write(output_path, &image)
+	{
+		println!("ERROR   could not write {}: {}", output_path, error);
+		return EXIT_ERROR;
+	}
+
+	println!("Assembled {} into {} ({} bytes).", source_path, output_path, image.len());
+
+	return EXIT_PASS;
+}
+
+// Lists the program of the platform's ROM, into the "output" of the "disassembler" block or on the console.
+pub fn run_disassemble(config: &Value) -> i32
+{
+	let settings = &config["disassembler"];
+	let rom_path = config["platform"]["rom"].as_str().unwrap_or("none");
+	let limit = settings["limit"].as_u64().unwrap_or(4096) as usize;
+
+	let image = match std::fs::read(rom_path)
+	{
+		Ok(image) => image,
+		Err(error) =>
+		{
+			println!("ERROR   could not read {}: {}", rom_path, error);
+			return EXIT_ERROR;
+		},
+	};
+
+	let listing = BYTEPUSHER::disassemble(&image, limit);
+
+	match settings["output"].as_str()
+	{
+		Some(output_path) =>
+		{
+			if let Err(error) = std::fs::write(output_path, listing)
+			{
+				println!("ERROR   could not write {}: {}", output_path, error);
+				return EXIT_ERROR;
+			}
+		},
+
+		None => print!("{}", listing),
+	}
+
+	return EXIT_PASS;
+}
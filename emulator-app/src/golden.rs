@@ -0,0 +1,300 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+
+use emulator_common::Image;
+use serde_json::Value;
+
+use crate::input_script::InputScript;
+use crate::platforms::create_system;
+
+// Colour of mismatched pixels in the diff image.
+const MISMATCH: u32 = 0xFF0000;
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String
+{
+	if let Some(message) = payload.downcast_ref::<String>()
+	{
+		return message.clone();
+	}
+
+	if let Some(message) = payload.downcast_ref::<&str>()
+	{
+		return message.to_string();
+	}
+
+	return "unknown panic".to_string();
+}
+
+enum CaseResult
+{
+	Pass,
+	Blessed,
+	Mismatch(String),
+	Error(String)
+}
+
+fn resolve(base: &Path, path: &str) -> String { return base.join(path).display().to_string(); }
+
+fn run_case(case: &Value, base: &Path) -> Result<Image, String>
+{
+	let rom = resolve(base, case["rom"].as_str().ok_or("missing rom")?);
+	let platform = case["platform"].as_str().unwrap_or("CHIP8");
+	let frames = case["frames"].as_u64().unwrap_or(60);
+	let seed = case["seed"].as_u64().unwrap_or(0);
+	let inputs = InputScript::from_json(&case["inputs"]);
+
+	// A core that panics only fails its own case.
+	let result = catch_unwind(AssertUnwindSafe(|| {
+		let mut emulator = create_system(platform, &case["backend_config"])?;
+		emulator.try_load(&rom)?;
+		emulator.seed_random(seed);
+
+		for frame in 0 .. frames
+		{
+			for event in inputs.events_for(frame)
+			{
+				if event.pressed
+				{
+					emulator.press_key(event.key);
+				}
+				else
+				{
+					emulator.release_key(event.key);
+				}
+			}
+
+			emulator.run_frame();
+		}
+
+		if let Some(fault) = emulator.get_fault()
+		{
+			return Err(format!("fault: {}", fault));
+		}
+
+		return Ok(emulator.frame().to_image());
+	}));
+
+	return match result
+	{
+		Ok(image) => image,
+		Err(payload) => Err(format!("crashed: {}", panic_message(payload.as_ref())))
+	};
+}
+
+// Expected on the left, actual in the middle and the mismatched pixels highlighted on the right.
+fn build_diff_image(expected: &Image, actual: &Image) -> (Image, usize)
+{
+	let width = expected.get_width().max(actual.get_width());
+	let height = expected.get_height().max(actual.get_height());
+	let mut result = Image::new(width * 3 + 2, height, 0x808080);
+	let mut mismatches = 0;
+
+	for y in 0 .. height
+	{
+		for x in 0 .. width
+		{
+			let expected_pixel = expected.get_pixel(x, y);
+			let actual_pixel = actual.get_pixel(x, y);
+
+			result.set_pixel(x, y, expected_pixel);
+			result.set_pixel(width + 1 + x, y, actual_pixel);
+
+			let same = x < expected.get_width().min(actual.get_width())
+				&& y < expected.get_height().min(actual.get_height())
+				&& expected_pixel == actual_pixel;
+
+			if same
+			{
+				// Dim matching pixels so the mismatches stand out.
+				result.set_pixel(2 * (width + 1) + x, y, (actual_pixel >> 2) & 0x3F3F3F);
+			}
+			else
+			{
+				result.set_pixel(2 * (width + 1) + x, y, MISMATCH);
+				mismatches += 1;
+			}
+		}
+	}
+
+	return (result, mismatches);
+}
+
+fn check_case(
+	case: &mut Value,
+	base: &Path,
+	output: &Path,
+	bless: bool
+) -> CaseResult
+{
+	let name = case["name"].as_str().unwrap_or("unnamed").to_string();
+
+	let actual = match run_case(case, base)
+	{
+		Ok(image) => image,
+		Err(error) => return CaseResult::Error(error)
+	};
+	let actual_hash = format!("{:016X}", actual.hash());
+
+	if bless
+	{
+		case["expected_hash"] = Value::String(actual_hash);
+
+		if let Some(reference) = case["reference_image"].as_str()
+		{
+			if let Err(error) = actual.save_ppm(&resolve(base, reference))
+			{
+				return CaseResult::Error(format!("could not write {}: {}", reference, error));
+			}
+		}
+
+		return CaseResult::Blessed;
+	}
+
+	let mut problems = Vec::new();
+
+	if let Some(expected_hash) = case["expected_hash"].as_str()
+	{
+		if !expected_hash.eq_ignore_ascii_case(&actual_hash)
+		{
+			problems.push(format!(
+				"hash {} instead of {}",
+				actual_hash, expected_hash
+			));
+		}
+	}
+
+	let mut expected_image = None;
+	if let Some(reference) = case["reference_image"].as_str()
+	{
+		match Image::load_ppm(&resolve(base, reference))
+		{
+			Ok(image) => expected_image = Some(image),
+			Err(error) => return CaseResult::Error(format!("could not read {}: {}", reference, error))
+		}
+	}
+
+	if let Some(expected) = &expected_image
+	{
+		let (diff, mismatches) = build_diff_image(expected, &actual);
+		if mismatches > 0
+		{
+			problems.push(format!("{} pixels differ", mismatches));
+
+			let diff_path = output.join(format!("{}.diff.png", name));
+			match diff.save_png(&diff_path.display().to_string())
+			{
+				Ok(_) => problems.push(format!("see {}", diff_path.display())),
+				Err(error) => error!("Could not save diff image: {}", error)
+			}
+		}
+	}
+	else if !problems.is_empty()
+	{
+		// Without a reference there is nothing to diff against, keep the actual frame around.
+		let actual_path = output.join(format!("{}.actual.png", name));
+		match actual.save_png(&actual_path.display().to_string())
+		{
+			Ok(_) => problems.push(format!("see {}", actual_path.display())),
+			Err(error) => error!("Could not save actual image: {}", error)
+		}
+	}
+
+	if case["expected_hash"].is_null() && expected_image.is_none()
+	{
+		return CaseResult::Error("no expectation, run with bless first".to_string());
+	}
+
+	if problems.is_empty()
+	{
+		return CaseResult::Pass;
+	}
+
+	return CaseResult::Mismatch(problems.join(", "));
+}
+
+// Runs every case of a golden manifest and compares the final frames with the expectations.
+pub fn run_golden(config: &Value) -> i32
+{
+	let settings = &config["golden"];
+	let manifest_path = PathBuf::from(settings["manifest"].as_str().unwrap_or("golden.json"));
+	let output = PathBuf::from(settings["output"].as_str().unwrap_or("golden-output"));
+	let bless = settings["bless"].as_bool().unwrap_or(false)
+		|| std::env::var("REMU_BLESS").is_ok_and(|value| value == "1");
+
+	let mut manifest: Value = match std::fs::read_to_string(&manifest_path)
+		.map_err(|error| error.to_string())
+		.and_then(|data| serde_json::from_str(&data).map_err(|error| error.to_string()))
+	{
+		Ok(manifest) => manifest,
+		Err(error) =>
+		{
+			println!("Could not read manifest {}: {}", manifest_path.display(), error);
+			return 2;
+		},
+	};
+
+	if let Err(error) = std::fs::create_dir_all(&output)
+	{
+		println!("Could not create {}: {}", output.display(), error);
+		return 2;
+	}
+
+	// Paths inside the manifest are relative to the manifest itself.
+	let base = manifest_path
+		.parent()
+		.map(|parent| parent.to_path_buf())
+		.unwrap_or_default();
+
+	let mut failures = 0;
+	let cases = match manifest["cases"].as_array_mut()
+	{
+		Some(cases) => cases,
+		None =>
+		{
+			println!("Manifest {} has no cases!", manifest_path.display());
+			return 2;
+		},
+	};
+
+	for case in cases.iter_mut()
+	{
+		let name = case["name"].as_str().unwrap_or("unnamed").to_string();
+
+		match check_case(case, &base, &output, bless)
+		{
+			CaseResult::Pass => println!("PASS    {}", name),
+			CaseResult::Blessed => println!("BLESSED {}", name),
+			CaseResult::Mismatch(problems) =>
+			{
+				failures += 1;
+				println!("FAIL    {}: {}", name, problems);
+			},
+			CaseResult::Error(error) =>
+			{
+				failures += 1;
+				println!("ERROR   {}: {}", name, error);
+			},
+		}
+	}
+
+	if bless
+	{
+		match std::fs::write(&manifest_path, serde_json::to_string_pretty(&manifest).unwrap())
+		{
+			Ok(_) => println!("Expectations written to {}.", manifest_path.display()),
+			Err(error) =>
+			{
+				println!("Could not write {}: {}", manifest_path.display(), error);
+				return 2;
+			},
+		}
+	}
+
+	if failures > 0
+	{
+		println!("{} golden cases failed.", failures);
+		return 1;
+	}
+
+	return 0;
+}
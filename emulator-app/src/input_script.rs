@@ -0,0 +1,79 @@
+use serde_json::Value;
+
+// A key change applied at the start of the given frame.
+pub struct InputEvent
+{
+	pub frame: u64,
+	pub key: u8,
+	pub pressed: bool
+}
+
+// Scripted keypad input for headless runs, read from entries like:
+// { "frame": 30, "press": [5], "release": [4] }
+pub struct InputScript
+{
+	events: Vec<InputEvent>
+}
+
+impl InputScript
+{
+	pub fn from_json(data: &Value) -> Self
+	{
+		let mut events = Vec::new();
+
+		for entry in data.as_array().unwrap_or(&Vec::new())
+		{
+			let frame = entry["frame"].as_u64().unwrap_or(0);
+
+			for (field, pressed) in [("press", true), ("release", false)]
+			{
+				for key in entry[field].as_array().unwrap_or(&Vec::new())
+				{
+					match key.as_u64()
+					{
+						Some(key) if key < 16 =>
+						{
+							events.push(InputEvent {
+								frame,
+								key: key as u8,
+								pressed
+							});
+						},
+						_ =>
+						{
+							warn!("Ignoring invalid scripted key: {}", key);
+						},
+					}
+				}
+			}
+		}
+
+		Self { events }
+	}
+
+	pub fn events_for(&self, frame: u64) -> impl Iterator<Item = &InputEvent>
+	{
+		return self.events.iter().filter(move |event| event.frame == frame);
+	}
+}
+
+// Overlays the fields of the second object onto the first one, recursing into nested objects.
+pub fn merge_json(base: &Value, overlay: &Value) -> Value
+{
+	let mut result = base.clone();
+
+	if let (Some(result_object), Some(overlay_object)) = (result.as_object_mut(), overlay.as_object())
+	{
+		for (key, value) in overlay_object
+		{
+			let merged = match result_object.get(key)
+			{
+				Some(existing) if existing.is_object() && value.is_object() => merge_json(existing, value),
+				_ => value.clone()
+			};
+			result_object.insert(key.clone(), merged);
+		}
+	}
+
+	return result;
+}
@@ -0,0 +1,71 @@
+use emulator_common::{DeltaTimer, Frontend, System};
+use emulator_frontends::{RaylibFrontend, RaylibFrontendConfig, TerminalFrontend, TerminalFrontendConfig};
+use serde_json::Value;
+
+use crate::audio_tools::AudioTools;
+use crate::memory_tools::{MemoryAccess, MemoryTools};
+
+// Opens the frontend named by "frontend" in the platform config, raylib if there is none.
+pub fn create_frontend(platform: &Value, system: &dyn System) -> Box<dyn Frontend>
+{
+	let frontend_config = &platform["frontend_config"];
+
+	match platform["frontend"].as_str().unwrap_or("raylib")
+	{
+		"terminal" =>
+		{
+			let ui_config = TerminalFrontendConfig::from_json(frontend_config, system.get_key_count());
+			return Box::new(TerminalFrontend::new(&ui_config));
+		},
+
+		"raylib" =>
+		{
+			let ui_config = RaylibFrontendConfig::from_json(frontend_config, system.get_key_count());
+			return Box::new(RaylibFrontend::new(&ui_config, system));
+		},
+
+		name =>
+		{
+			error!("Invalid frontend specified: {}!", name);
+			panic!("Invalid frontend specified: {}!", name);
+		},
+	}
+}
+
+// The one main loop every platform runs in. after_update is called once per iteration,
+// right after the emulator has advanced, for platform specific tooling.
+pub fn run_interactive<S>(
+	emulator: &mut S,
+	user_interface: &mut dyn Frontend,
+	memory_tools: &MemoryTools,
+	audio_tools: &mut AudioTools,
+	mut after_update: impl FnMut(&mut S)
+) where
+	S: System + MemoryAccess
+{
+	let mut delta_timer = DeltaTimer::new();
+
+	while !user_interface.has_quit()
+	{
+		delta_timer.update();
+
+		user_interface.update(emulator, delta_timer.get());
+		for command in user_interface.take_commands()
+		{
+			memory_tools.handle(command, emulator);
+		}
+		emulator.update(delta_timer.get());
+		after_update(emulator);
+
+		if let Some(samples) = emulator.take_audio()
+		{
+			user_interface.play_audio(&samples);
+			audio_tools.record(&samples);
+		}
+
+		user_interface.draw(emulator);
+	}
+
+	memory_tools.finish(emulator);
+	audio_tools.finish();
+}
@@ -0,0 +1,29 @@
+// The app's modes as a library, so integration tests can run them without going through the binary.
+#[macro_use]
+extern crate log;
+
+extern crate serde_json;
+
+pub mod memory_tools;
+
+pub mod audio_tools;
+
+pub mod input_script;
+
+pub mod interactive;
+
+pub mod platforms;
+
+pub mod lockstep;
+
+pub mod batch;
+
+pub mod test_runner;
+
+pub mod golden;
+
+pub mod benchmark;
+
+pub mod bytepusher_tools;
+
+pub mod bytepusher_debugger;
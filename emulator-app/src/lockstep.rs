@@ -0,0 +1,79 @@
+use emulator_chip8 as CHIP8;
+use serde_json::Value;
+
+use crate::input_script::{merge_json, InputScript};
+
+// Runs two CHIP8 configurations side by side until they stop agreeing.
+// Returns the process exit code, 0 if no divergence was found.
+pub fn run_lockstep(config: &Value) -> i32
+{
+	let platform = &config["platform"];
+	let settings = &config["lockstep"];
+
+	if platform["name"].as_str().unwrap_or("none").to_uppercase() != "CHIP8"
+	{
+		error!("Lockstep mode only supports the CHIP8 platform!");
+		println!("Lockstep mode only supports the CHIP8 platform!");
+		return 2;
+	}
+
+	let left_json = &platform["backend_config"];
+	let right_json = merge_json(left_json, &settings["backend_config"]);
+	let rom = platform["rom"].as_str().unwrap_or("none");
+	let seed = settings["seed"].as_u64().unwrap_or(0);
+
+	let mut emulators = Vec::new();
+	for backend_config in [left_json, &right_json]
+	{
+		let mut emulator =
+			CHIP8::Emulator::new(&CHIP8::Configs::EmulatorConfig::from_json(backend_config));
+		emulator.load(rom);
+		emulator.seed_random(seed);
+		emulators.push(emulator);
+	}
+
+	let right = emulators.pop().unwrap();
+	let left = emulators.pop().unwrap();
+	println!("Left quirks:  {:?}", left.get_quirks());
+	println!("Right quirks: {:?}", right.get_quirks());
+
+	let frames = settings["frames"].as_u64().unwrap_or(600);
+	let inputs = InputScript::from_json(&settings["inputs"]);
+	let mut lockstep = CHIP8::Lockstep::new(left, right);
+
+	for frame in 0 .. frames
+	{
+		for event in inputs.events_for(frame)
+		{
+			if event.pressed
+			{
+				lockstep.press_key(event.key);
+			}
+			else
+			{
+				lockstep.release_key(event.key);
+			}
+		}
+
+		if let Some(divergence) = lockstep.run_frame()
+		{
+			let report = lockstep.report(&divergence);
+			info!("{}", report);
+			println!("{}", report);
+			return 1;
+		}
+
+		if !lockstep.is_running()
+		{
+			break;
+		}
+	}
+
+	println!(
+		"No divergence after {} frames ({} instructions).",
+		lockstep.get_frames(),
+		lockstep.get_steps()
+	);
+
+	return 0;
+}
@@ -7,6 +7,17 @@ use std::fs::{read_to_string, OpenOptions};
 
 use serde_json::Value;
 
+use emulator_app::audio_tools::AudioTools;
+use emulator_app::batch::run_batch;
+use emulator_app::benchmark::run_benchmark;
+use emulator_app::bytepusher_debugger::run_debugger;
+use emulator_app::bytepusher_tools::{run_assemble, run_disassemble};
+use emulator_app::golden::run_golden;
+use emulator_app::interactive::{create_frontend, run_interactive};
+use emulator_app::lockstep::run_lockstep;
+use emulator_app::memory_tools::MemoryTools;
+use emulator_app::test_runner::run_test;
+
 fn setup_logging(extensive_logging: bool)
 {
 	use env_logger::{Builder, Env, Target};
@@ -34,7 +45,7 @@ fn setup_logging(extensive_logging: bool)
 
 use emulator_bytepusher as BYTEPUSHER;
 use emulator_chip8 as CHIP8;
-use emulator_common::DeltaTimer;
+use emulator_common::color_from_json;
 
 fn setup_bytepusher(platform: &Value)
 {
@@ -45,20 +56,19 @@ fn setup_bytepusher(platform: &Value)
 
 	info!("Emulator backend setup completed successfully.");
 
-	// UI setup.
-	let mut delta_timer = DeltaTimer::new();
-	let ui_config = BYTEPUSHER::RaylibFrontendConfig::from_json(&platform["frontend_config"]);
-	let mut user_interface = BYTEPUSHER::RaylibFrontend::new(&ui_config);
-
-	while !user_interface.has_quit()
-	{
-		delta_timer.update();
-
-		user_interface.update(&mut emulator, delta_timer.get());
-		emulator.update(delta_timer.get());
+	let memory_tools = MemoryTools::from_json(&platform["memory_tools"]);
+	let mut audio_tools = AudioTools::from_json(&platform["audio_tools"]);
 
-		user_interface.draw(&mut emulator);
-	}
+	// UI setup.
+	let mut user_interface = create_frontend(platform, &emulator);
+
+	run_interactive(
+		&mut emulator,
+		user_interface.as_mut(),
+		&memory_tools,
+		&mut audio_tools,
+		|_| {}
+	);
 }
 
 fn setup_chip8(platform: &Value)
@@ -68,48 +78,67 @@ fn setup_chip8(platform: &Value)
 	let mut emulator = CHIP8::Emulator::new(&config);
 	emulator.load(platform["rom"].as_str().unwrap_or("none"));
 
+	// CHIP8 is monochrome, the frontend config picks the two colours.
+	let frontend_config = &platform["frontend_config"];
+	emulator.set_colors(
+		color_from_json(&frontend_config["foreground"], 0xFFFFFF),
+		color_from_json(&frontend_config["background"], 0x000000)
+	);
+
 	info!("Emulator backend setup completed successfully.");
 
-	// UI setup.
-	let mut delta_timer = DeltaTimer::new();
-	match platform["frontend"].as_str().unwrap_or("none")
-	{
-		"terminal" =>
-		{
-			let ui_config = CHIP8::TerminalFrontendConfig::from_json(&platform["frontend_config"]);
-			let mut user_interface = CHIP8::TerminalFrontend::new(&ui_config);
+	// Sprite tools setup.
+	let sprite_tools = &platform["sprite_tools"];
+	let mut sprite_ripper = CHIP8::SpriteRipper::new();
+	emulator.set_draw_logging(sprite_tools["ripper_output"].is_string());
 
-			while !user_interface.has_quit()
-			{
-				delta_timer.update();
+	let memory_tools = MemoryTools::from_json(&platform["memory_tools"]);
+	let mut audio_tools = AudioTools::from_json(&platform["audio_tools"]);
 
-				user_interface.update(&mut emulator, delta_timer.get());
-				emulator.update(delta_timer.get());
+	// UI setup.
+	let mut user_interface = create_frontend(platform, &emulator);
 
-				user_interface.draw(&mut emulator);
-			}
-		},
+	run_interactive(
+		&mut emulator,
+		user_interface.as_mut(),
+		&memory_tools,
+		&mut audio_tools,
+		|emulator| sprite_ripper.record(emulator.take_draw_log())
+	);
 
-		"raylib" =>
-		{
-			let ui_config = CHIP8::RaylibFrontendConfig::from_json(&platform["frontend_config"]);
-			let mut user_interface = CHIP8::RaylibFrontend::new(&ui_config);
+	export_chip8_sprites(&emulator, &sprite_ripper, sprite_tools);
+}
 
-			while !user_interface.has_quit()
-			{
-				delta_timer.update();
+fn export_chip8_sprites(emulator: &CHIP8::Emulator, ripper: &CHIP8::SpriteRipper, tools: &Value)
+{
+	let sheet_config = CHIP8::SpriteSheetConfig::from_json(tools);
 
-				user_interface.update(&mut emulator, delta_timer.get());
-				emulator.update(delta_timer.get());
+	// Sprites drawn by the program during the session.
+	if let Some(path) = tools["ripper_output"].as_str()
+	{
+		info!(
+			"Ripped {} unique sprites out of {} draws.",
+			ripper.get_sprites().len(),
+			ripper.get_draw_count()
+		);
 
-				user_interface.draw(&mut emulator);
-			}
-		},
+		if let Err(error) = ripper.to_image(&sheet_config).save_png(path)
+		{
+			error!("Could not save sprite sheet to {}: {}", path, error);
+		}
+	}
 
-		_ =>
+	// Raw view of a memory range, regardless of what was drawn.
+	if let Some(path) = tools["viewer_output"].as_str()
+	{
+		let start = tools["viewer_start"].as_u64().unwrap_or(0) as usize;
+		let length = tools["viewer_length"].as_u64().unwrap_or(0x1000) as usize;
+
+		if let Err(error) = emulator
+			.render_memory_sprites(start, length, &sheet_config)
+			.save_png(path)
 		{
-			error!("Invalid CHIP8 frontend specified!");
-			panic!("Invalid CHIP8 frontend specified!");
+			error!("Could not save memory view to {}: {}", path, error);
 		}
 	}
 }
@@ -149,11 +178,69 @@ fn main()
 
 	let config_path = arguments.unwrap();
 	let argument_data = read_to_string(config_path.clone()).expect("Could not read config file!");
-	let json_data: Value = serde_json::from_str(&argument_data).unwrap();
+	let mut json_data: Value = serde_json::from_str(&argument_data).unwrap();
+
+	// An optional second argument overrides the ROM, so one config can drive many ROMs.
+	if let Some(rom) = std::env::args().nth(2)
+	{
+		json_data["platform"]["rom"] = Value::String(rom);
+	}
 
 	setup_logging(json_data["extensive_logging"].as_bool().unwrap_or(false));
 
 	info!("Used config from path {}.", config_path.clone());
 
-	setup_emulator(&json_data["platform"]);
+	match json_data["mode"].as_str().unwrap_or("interactive")
+	{
+		"interactive" =>
+		{
+			setup_emulator(&json_data["platform"]);
+		},
+
+		"lockstep" =>
+		{
+			std::process::exit(run_lockstep(&json_data));
+		},
+
+		"batch" =>
+		{
+			std::process::exit(run_batch(&json_data));
+		},
+
+		"test" =>
+		{
+			std::process::exit(run_test(&json_data));
+		},
+
+		"golden" =>
+		{
+			std::process::exit(run_golden(&json_data));
+		},
+
+		"benchmark" =>
+		{
+			std::process::exit(run_benchmark(&json_data));
+		},
+
+		"assemble" =>
+		{
+			std::process::exit(run_assemble(&json_data));
+		},
+
+		"disassemble" =>
+		{
+			std::process::exit(run_disassemble(&json_data));
+		},
+
+		"debug" =>
+		{
+			std::process::exit(run_debugger(&json_data));
+		},
+
+		mode =>
+		{
+			error!("Invalid mode specified: {}!", mode);
+			panic!("Invalid mode specified: {}!", mode);
+		},
+	}
 }
@@ -0,0 +1,104 @@
+use emulator_bytepusher as BYTEPUSHER;
+use emulator_chip8 as CHIP8;
+use emulator_common::FrontendCommand;
+use serde_json::Value;
+
+// Memory access every platform provides, used by the dump / inject commands.
+pub trait MemoryAccess
+{
+	fn dump_memory(&self, path: &str) -> std::io::Result<()>;
+
+	fn inject_memory(&mut self, path: &str, address: usize) -> std::io::Result<()>;
+}
+
+impl MemoryAccess for CHIP8::Emulator
+{
+	fn dump_memory(&self, path: &str) -> std::io::Result<()>
+	{
+		return CHIP8::Emulator::dump_memory(self, path);
+	}
+
+	fn inject_memory(&mut self, path: &str, address: usize) -> std::io::Result<()>
+	{
+		return CHIP8::Emulator::inject_memory(self, path, address);
+	}
+}
+
+impl MemoryAccess for BYTEPUSHER::Emulator
+{
+	fn dump_memory(&self, path: &str) -> std::io::Result<()>
+	{
+		return BYTEPUSHER::Emulator::dump_memory(self, path);
+	}
+
+	fn inject_memory(&mut self, path: &str, address: usize) -> std::io::Result<()>
+	{
+		return BYTEPUSHER::Emulator::inject_memory(self, path, address);
+	}
+}
+
+pub struct MemoryTools
+{
+	dump_path: String,
+	dump_at_exit: bool,
+	inject_path: Option<String>,
+	inject_address: usize
+}
+
+impl MemoryTools
+{
+	pub fn from_json(data: &Value) -> Self
+	{
+		Self {
+			dump_path: data["dump_path"]
+				.as_str()
+				.unwrap_or("memory.bin")
+				.to_string(),
+			dump_at_exit: data["dump_at_exit"].as_bool().unwrap_or(false),
+			inject_path: data["inject_path"].as_str().map(|path| path.to_string()),
+			inject_address: data["inject_address"].as_u64().unwrap_or(0) as usize
+		}
+	}
+
+	pub fn handle<T: MemoryAccess>(&self, command: FrontendCommand, emulator: &mut T)
+	{
+		match command
+		{
+			FrontendCommand::DumpMemory => self.dump(emulator),
+
+			FrontendCommand::InjectMemory =>
+			{
+				let path = match &self.inject_path
+				{
+					Some(path) => path,
+					None =>
+					{
+						warn!("Memory inject requested, but no inject_path is configured.");
+						return;
+					},
+				};
+
+				if let Err(error) = emulator.inject_memory(path, self.inject_address)
+				{
+					error!("Could not inject {}: {}", path, error);
+				}
+			},
+		}
+	}
+
+	pub fn finish<T: MemoryAccess>(&self, emulator: &T)
+	{
+		if self.dump_at_exit
+		{
+			self.dump(emulator);
+		}
+	}
+
+	fn dump<T: MemoryAccess>(&self, emulator: &T)
+	{
+		if let Err(error) = emulator.dump_memory(&self.dump_path)
+		{
+			error!("Could not dump memory to {}: {}", self.dump_path, error);
+		}
+	}
+}
@@ -0,0 +1,25 @@
+use emulator_bytepusher as BYTEPUSHER;
+use emulator_chip8 as CHIP8;
+use emulator_common::System;
+use serde_json::Value;
+
+// Builds the emulator for a platform name from the config, without loading anything.
+pub fn create_system(name: &str, backend_config: &Value) -> Result<Box<dyn System>, String>
+{
+	return match name.to_uppercase().as_str()
+	{
+		"CHIP8" =>
+		{
+			let config = CHIP8::Configs::EmulatorConfig::from_json(backend_config);
+			Ok(Box::new(CHIP8::Emulator::new(&config)))
+		},
+
+		"BYTEPUSHER" =>
+		{
+			let config = BYTEPUSHER::Configs::EmulatorConfig::from_json(backend_config);
+			Ok(Box::new(BYTEPUSHER::Emulator::new(&config)))
+		},
+
+		_ => Err(format!("unsupported platform {}", name))
+	};
+}
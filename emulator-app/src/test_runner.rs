@@ -0,0 +1,164 @@
+use emulator_chip8 as CHIP8;
+use serde_json::Value;
+
+use crate::input_script::InputScript;
+
+pub const EXIT_PASS: i32 = 0;
+pub const EXIT_FAIL: i32 = 1;
+pub const EXIT_ERROR: i32 = 2;
+
+// Where the ROM leaves its verdict once it is done.
+enum ResultSource
+{
+	Register(usize),
+	Address(usize)
+}
+
+impl ResultSource
+{
+	fn from_json(data: &Value) -> Option<Self>
+	{
+		if let Some(register) = data["register"].as_u64()
+		{
+			if register < 16
+			{
+				return Some(Self::Register(register as usize));
+			}
+		}
+
+		if let Some(address) = data["address"].as_u64()
+		{
+			return Some(Self::Address(address as usize));
+		}
+
+		return None;
+	}
+
+	fn read(&self, emulator: &CHIP8::Emulator) -> Option<u8>
+	{
+		return match self
+		{
+			Self::Register(register) => Some(emulator.get_registers()[*register]),
+			Self::Address(address) => emulator.get_memory().get(*address).copied()
+		};
+	}
+
+	fn describe(&self) -> String
+	{
+		return match self
+		{
+			Self::Register(register) => format!("V{:X}", register),
+			Self::Address(address) => format!("RAM[{:#05X}]", address)
+		};
+	}
+}
+
+// Runs a test ROM headlessly until it halts, then turns its result into an exit code.
+pub fn run_test(config: &Value) -> i32
+{
+	let platform = &config["platform"];
+	let settings = &config["test"];
+
+	if platform["name"].as_str().unwrap_or("none").to_uppercase() != "CHIP8"
+	{
+		error!("Test mode only supports the CHIP8 platform!");
+		println!("Test mode only supports the CHIP8 platform!");
+		return EXIT_ERROR;
+	}
+
+	let source = match ResultSource::from_json(&settings["result"])
+	{
+		Some(source) => source,
+		None =>
+		{
+			error!("Test mode needs a result register (0-15) or address!");
+			println!("Test mode needs a result register (0-15) or address!");
+			return EXIT_ERROR;
+		},
+	};
+
+	let rom = platform["rom"].as_str().unwrap_or("none");
+	let frames = settings["frames"].as_u64().unwrap_or(600);
+	let pass_value = settings["pass_value"].as_u64().unwrap_or(0) as u8;
+	let require_halt = settings["require_halt"].as_bool().unwrap_or(true);
+	let inputs = InputScript::from_json(&settings["inputs"]);
+
+	let config = CHIP8::Configs::EmulatorConfig::from_json(&platform["backend_config"]);
+	let mut emulator = CHIP8::Emulator::new(&config);
+	emulator.seed_random(settings["seed"].as_u64().unwrap_or(0));
+
+	if let Err(error) = emulator.try_load(rom)
+	{
+		error!("{}", error);
+		println!("{}", error);
+		return EXIT_ERROR;
+	}
+
+	let mut halted_at = None;
+	for frame in 0 .. frames
+	{
+		for event in inputs.events_for(frame)
+		{
+			if event.pressed
+			{
+				emulator.press_key(event.key);
+			}
+			else
+			{
+				emulator.release_key(event.key);
+			}
+		}
+
+		emulator.run_frame();
+
+		if !emulator.is_running()
+		{
+			halted_at = Some(frame);
+			break;
+		}
+	}
+
+	// A faulting ROM is a failed test, not a crashed runner.
+	if let Some(fault) = emulator.get_fault()
+	{
+		println!("FAIL {}: {}", rom, fault);
+		return EXIT_FAIL;
+	}
+
+	match halted_at
+	{
+		Some(frame) => info!("Test ROM halted at frame {}.", frame),
+		None if require_halt =>
+		{
+			println!("FAIL {}: did not halt within {} frames", rom, frames);
+			return EXIT_FAIL;
+		},
+		None => info!("Test ROM reached the frame limit without halting."),
+	}
+
+	let value = match source.read(&emulator)
+	{
+		Some(value) => value,
+		None =>
+		{
+			println!("FAIL {}: {} is out of range", rom, source.describe());
+			return EXIT_FAIL;
+		},
+	};
+
+	if value != pass_value
+	{
+		println!(
+			"FAIL {}: {} = {:#04X}, expected {:#04X}",
+			rom,
+			source.describe(),
+			value,
+			pass_value
+		);
+		return EXIT_FAIL;
+	}
+
+	println!("PASS {}: {} = {:#04X}", rom, source.describe(), value);
+
+	return EXIT_PASS;
+}
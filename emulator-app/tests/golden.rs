@@ -0,0 +1,49 @@
+use emulator_app::golden::run_golden;
+use serde_json::{json, Value};
+
+fn manifest_path() -> String { return format!("{}/../golden/manifest.json", env!("CARGO_MANIFEST_DIR")); }
+
+fn run_manifest(manifest: &str, name: &str) -> i32
+{
+	let output = std::env::temp_dir().join(format!("remu-golden-{}-{}", std::process::id(), name));
+
+	let result = run_golden(&json!({
+		"golden": {
+			"manifest": manifest,
+			"output": output.to_str().unwrap()
+		}
+	}));
+
+	let _ = std::fs::remove_dir_all(&output);
+
+	return result;
+}
+
+#[test]
+fn committed_cases_match()
+{
+	let result = run_manifest(&manifest_path(), "committed");
+	assert_eq!(result, 0, "a golden case changed, bless it again if that was intended");
+}
+
+#[test]
+fn changed_frame_is_a_mismatch()
+{
+	let mut manifest: Value = serde_json::from_str(&std::fs::read_to_string(manifest_path()).unwrap()).unwrap();
+	let roms = format!("{}/../golden", env!("CARGO_MANIFEST_DIR"));
+
+	// Same ROMs, but expecting a frame they don't draw.
+	for case in manifest["cases"].as_array_mut().unwrap()
+	{
+		case["rom"] = Value::String(format!("{}/{}", roms, case["rom"].as_str().unwrap()));
+		case["expected_hash"] = Value::String("0000000000000000".to_string());
+	}
+
+	let path = std::env::temp_dir().join(format!("remu-golden-{}-mismatch.json", std::process::id()));
+	std::fs::write(&path, manifest.to_string()).unwrap();
+
+	let result = run_manifest(path.to_str().unwrap(), "mismatch");
+	let _ = std::fs::remove_file(&path);
+
+	assert_eq!(result, 1);
+}
@@ -0,0 +1,47 @@
+use emulator_app::test_runner::{run_test, EXIT_FAIL, EXIT_PASS};
+use serde_json::json;
+
+// Sets VF to the given value, then jumps to itself, which the CPU treats as halting.
+fn verdict_rom(value: u8) -> Vec<u8> { return vec![0x6F, value, 0x12, 0x02]; }
+
+fn run_rom(name: &str, rom: &[u8]) -> i32
+{
+	let path = std::env::temp_dir().join(format!("remu-test-runner-{}-{}.ch8", std::process::id(), name));
+	std::fs::write(&path, rom).unwrap();
+
+	let config = json!({
+		"platform": {
+			"name": "CHIP8",
+			"rom": path.to_str().unwrap()
+		},
+		"test": {
+			"frames": 60,
+			"result": { "register": 15 },
+			"pass_value": 0
+		}
+	});
+
+	let result = run_test(&config);
+	let _ = std::fs::remove_file(&path);
+
+	return result;
+}
+
+#[test]
+fn passing_rom_passes()
+{
+	assert_eq!(run_rom("pass", &verdict_rom(0)), EXIT_PASS);
+}
+
+#[test]
+fn failing_rom_fails()
+{
+	assert_eq!(run_rom("fail", &verdict_rom(1)), EXIT_FAIL);
+}
+
+#[test]
+fn rom_that_never_halts_fails()
+{
+	// Counts V0 up forever, never reaching the jump to itself.
+	assert_eq!(run_rom("loop", &[0x6F, 0x00, 0x70, 0x01, 0x12, 0x02]), EXIT_FAIL);
+}
@@ -1,32 +1,49 @@
 mod system;
-use std::io::Read;
+use std::io::{Error, ErrorKind, Read};
+
+use emulator_common::{load_memory_file, save_memory_file, Framebuffer, Image, SampleBuffer, System};
 
 pub use system::components as Components;
 pub use system::configs as Configs;
 
-mod user_interfaces;
-pub use user_interfaces::*;
+mod tools;
+pub use tools::*;
 
 extern crate pretty_env_logger;
 #[macro_use]
 extern crate log;
 
+// 256 samples per frame at 60 frames per second.
+pub const AUDIO_RATE: u32 = 15360;
+
 pub struct Emulator
 {
 	cpu: Components::CPU,
 	ram: Components::RAM,
-	keyboard: Components::Keyboard
+	keyboard: Components::Keyboard,
+	snapshot: Components::FrameSnapshot,
+
+	config: Configs::EmulatorConfig,
+	rom: Vec<u8>,
+	framebuffer: Framebuffer
 }
 
 impl Emulator
 {
 	pub fn new(config: &Configs::EmulatorConfig) -> Self
 	{
-		Self {
+		let mut result = Self {
 			cpu: Components::CPU::new(&config.cpu_config),
-			ram: Components::RAM::new(&config.ram_config),
-			keyboard: Components::Keyboard::new()
-		}
+			ram: Components::RAM::new(),
+			keyboard: Components::Keyboard::new(),
+			snapshot: Components::FrameSnapshot::new(),
+			config: config.clone(),
+			rom: Vec::new(),
+			framebuffer: Framebuffer::new(256, 256)
+		};
+		result.set_palette(&config.palette);
+
+		return result;
 	}
 
 	#[inline]
@@ -59,13 +76,44 @@ impl Emulator
 	#[inline]
 	pub fn load(&mut self, path: &str)
 	{
-		let mut rom = std::fs::File::open(path).expect("Unable to open ROM for loading!");
+		if let Err(error) = self.try_load(path)
+		{
+			error!("{}", error);
+			panic!("{}", error);
+		}
+	}
 
+	// Like load, but reports unreadable or oversized ROMs instead of panicking.
+	pub fn try_load(&mut self, path: &str) -> Result<(), String>
+	{
 		let mut buffer: Vec<u8> = Vec::new();
-		rom.read_to_end(&mut buffer).unwrap();
+		std::fs::File::open(path)
+			.and_then(|mut rom| rom.read_to_end(&mut buffer))
+			.map_err(|error| format!("Unable to open ROM {} for loading: {}", path, error))?;
 		info!("Read ROM from path: {}", path);
 
-		self.ram.load_rom_data(&mut buffer);
+		return self.load_data(&buffer);
+	}
+
+	pub fn load_data(&mut self, data: &[u8]) -> Result<(), String>
+	{
+		self.ram.load_rom_data(data)?;
+		self.rom = data.to_vec();
+
+		return Ok(());
+	}
+
+	// Back to the power-on state with the last loaded ROM, the palette stays as it was.
+	pub fn reset(&mut self)
+	{
+		let rom = std::mem::take(&mut self.rom);
+		let palette = self.ram.get_colormap().to_vec();
+		let config = self.config.clone();
+		*self = Self::new(&config);
+		self.set_palette(&palette);
+
+		// The ROM fitted before, so it still does.
+		let _ = self.load_data(&rom);
 	}
 
 	#[inline]
@@ -74,12 +122,136 @@ impl Emulator
 	#[inline]
 	pub fn get_display_height(&self) -> usize { return 256; }
 
+	// Picks the 0xRRGGBB colour of each of the 256 pixel values, entries past the end of the palette are black.
+	#[inline]
+	pub fn set_palette(&mut self, palette: &[u32]) { self.ram.set_colormap(palette); }
+
+	// The screen as it was when the last frame finished.
 	#[inline]
 	pub fn get_display_pixel(&self, x: usize, y: usize) -> u32
 	{
-		let address = ((self.ram.read_byte(5) as usize) << 16) | (y << 8) as usize | x as usize;
+		return self.ram.get_color_value(self.snapshot.get_pixel(x, y));
+	}
+
+	// The pixel bank and audio bank captured when the last frame finished.
+	#[inline]
+	pub fn get_snapshot(&self) -> &Components::FrameSnapshot { return &self.snapshot; }
+
+	// Refreshes the framebuffer in one pass from the last frame's pixels through the colour map.
+	pub fn frame(&mut self) -> &Framebuffer
+	{
+		let colors = self.ram.get_colormap_bytes();
+
+		for (pixel, index) in self
+			.framebuffer
+			.get_data_mut()
+			.chunks_exact_mut(4)
+			.zip(self.snapshot.get_pixels())
+		{
+			pixel.copy_from_slice(&colors[*index as usize]);
+		}
+
+		return &self.framebuffer;
+	}
+
+	// Renders the current screen through the colour map.
+	pub fn get_display_image(&self) -> Image
+	{
+		let mut result = Image::new(self.get_display_width(), self.get_display_height(), 0);
+
+		for y in 0 .. self.get_display_height()
+		{
+			for x in 0 .. self.get_display_width()
+			{
+				result.set_pixel(x, y, self.get_display_pixel(x, y));
+			}
+		}
+
+		return result;
+	}
+
+	// Runs a whole frame as fast as possible, for use without a frontend.
+	#[inline]
+	pub fn run_frame(&mut self) { self.cpu.step(&mut self.ram, &self.keyboard, &mut self.snapshot); }
+
+	// Runs the next single instruction, see the Debugger for stopping on breakpoints and watched writes.
+	#[inline]
+	pub fn step_instruction(&mut self) -> Components::MemoryWrite
+	{
+		return self.cpu.step_instruction(&mut self.ram, &self.keyboard, &mut self.snapshot);
+	}
+
+	// The next instruction to run. At a frame boundary that is the reset vector, which the frame
+	// reads when it starts.
+	#[inline]
+	pub fn get_pc(&self) -> u32
+	{
+		if self.cpu.get_frame_position() == 0
+		{
+			return self.ram.read_triple_byte(2);
+		}
+
+		return self.cpu.get_pc();
+	}
+
+	// Instructions of the current frame already run, 0 at a frame boundary.
+	#[inline]
+	pub fn get_frame_position(&self) -> usize { return self.cpu.get_frame_position(); }
+
+	// The 16 MiB address space as the program sees it right now.
+	#[inline]
+	pub fn get_memory(&self) -> &[u8] { return self.ram.get_data(); }
+
+	// Signed PCM at AUDIO_RATE, everything the frames since the last call played.
+	#[inline]
+	pub fn take_audio(&mut self) -> SampleBuffer { return self.snapshot.take_audio(); }
+
+	// Whether the last frame's sound has anything but silence in it.
+	#[inline]
+	pub fn is_sound_playing(&self) -> bool { return self.snapshot.get_audio_frame().iter().any(|sample| *sample != 0); }
+
+	// Writes the whole RAM to disk, as Intel HEX if the path ends in .hex and raw binary otherwise.
+	#[inline]
+	pub fn dump_memory(&self, path: &str) -> std::io::Result<()>
+	{
+		return save_memory_file(path, self.ram.get_data());
+	}
+
+	// Loads a binary or Intel HEX file into RAM at the given address while running.
+	pub fn inject_memory(&mut self, path: &str, address: usize) -> std::io::Result<()>
+	{
+		let segments = load_memory_file(path)?;
+
+		// Validate everything first so a bad file doesn't leave memory half patched.
+		for segment in &segments
+		{
+			// A huge inject address must not wrap around past the check.
+			let end = address
+				.checked_add(segment.offset)
+				.and_then(|start| start.checked_add(segment.data.len()));
+
+			if end.is_none_or(|end| end > self.ram.get_size())
+			{
+				return Err(Error::new(
+					ErrorKind::InvalidInput,
+					format!(
+						"{} bytes at {:#04X} + {:#04X} do not fit in RAM",
+						segment.data.len(),
+						address,
+						segment.offset
+					)
+				));
+			}
+		}
+
+		for segment in &segments
+		{
+			self.ram.write_data(address + segment.offset, &segment.data);
+		}
+
+		info!("Injected {} at address {:#04X}.", path, address);
 
-		return self.ram.get_color_value(self.ram.read_byte(address as u32));
+		return Ok(());
 	}
 
 	// Updates the emulator state by the given ammount of seconds.
@@ -90,6 +262,58 @@ impl Emulator
 		{
 			return;
 		}
-		self.cpu.update(&mut self.ram, &mut self.keyboard, delta);
+		self.cpu.update(&mut self.ram, &mut self.keyboard, &mut self.snapshot, delta);
+	}
+}
+
+impl System for Emulator
+{
+	fn try_load(&mut self, path: &str) -> Result<(), String> { return Emulator::try_load(self, path); }
+
+	fn load_data(&mut self, data: &[u8]) -> Result<(), String> { return Emulator::load_data(self, data); }
+
+	fn reset(&mut self) { Emulator::reset(self); }
+
+	fn update(&mut self, delta: f64) { Emulator::update(self, delta); }
+
+	fn run_frame(&mut self) { Emulator::run_frame(self); }
+
+	fn get_key_count(&self) -> usize { return 16; }
+
+	fn press_key(&mut self, key: u8) { Emulator::press_key(self, key); }
+
+	fn release_key(&mut self, key: u8) { Emulator::release_key(self, key); }
+
+	fn get_screen_width(&self) -> usize { return self.get_display_width(); }
+
+	fn get_screen_height(&self) -> usize { return self.get_display_height(); }
+
+	fn get_screen_pixel(&self, x: usize, y: usize) -> u32 { return self.get_display_pixel(x, y); }
+
+	fn frame(&mut self) -> &Framebuffer { return Emulator::frame(self); }
+
+	fn is_sound_playing(&self) -> bool { return Emulator::is_sound_playing(self); }
+
+	fn take_audio(&mut self) -> Option<SampleBuffer> { return Some(Emulator::take_audio(self)); }
+
+	fn is_running(&self) -> bool { return Emulator::is_running(self); }
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn injecting_at_a_huge_address_is_an_error()
+	{
+		let path = std::env::temp_dir().join(format!("inject-bytepusher-{}.bin", std::process::id()));
+		std::fs::write(&path, [1, 2, 3]).unwrap();
+
+		let mut emulator = Emulator::new(&Configs::EmulatorConfig::default());
+		let result = emulator.inject_memory(path.to_str().unwrap(), usize::MAX - 1);
+		let _ = std::fs::remove_file(&path);
+
+		assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidInput);
 	}
 }
@@ -1,10 +1,26 @@
 use emulator_common::{clamp, sleep_seconds_f64, GenericTimer};
 
+use crate::system::components::ram::{read_address, ADDRESS_MASK};
 use crate::{Components, Configs};
 
+pub const INSTRUCTIONS_PER_FRAME: usize = 65536;
+
+// The byte a single instruction wrote, as the debugger sees it.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct MemoryWrite
+{
+	pub pc: u32,
+	pub source: u32,
+	pub destination: u32,
+	pub old_value: u8,
+	pub new_value: u8
+}
+
 pub struct CPU
 {
 	pc: u32,
+	// Instructions of the current frame already run, 0 at a frame boundary.
+	position: usize,
 
 	halt_flag: bool,
 
@@ -17,6 +33,7 @@ impl CPU
 	{
 		Self {
 			pc: 0,
+			position: 0,
 			timer: GenericTimer::new(&config.timer),
 			halt_flag: false
 		}
@@ -25,25 +42,114 @@ impl CPU
 	#[inline]
 	pub fn halted(&self) -> bool { return self.halt_flag; }
 
+	// The next instruction to run inside a frame. Once a frame ended the next one starts at the reset vector instead.
+	#[inline]
+	pub fn get_pc(&self) -> u32 { return self.pc; }
+
 	#[inline]
-	pub fn step(&mut self, ram: &mut Components::RAM)
+	pub fn get_frame_position(&self) -> usize { return self.position; }
+
+	// One whole frame: the keys go in, exactly 65536 instructions run and the screen and sound come out.
+	// A frame the debugger stopped in the middle of is finished from where it stopped.
+	#[inline]
+	pub fn step(
+		&mut self,
+		ram: &mut Components::RAM,
+		keyboard: &Components::Keyboard,
+		snapshot: &mut Components::FrameSnapshot
+	)
 	{
+		if self.position == 0
+		{
+			self.begin_frame(ram, keyboard);
+		}
+
+		self.run_instructions(ram, INSTRUCTIONS_PER_FRAME - self.position);
+		self.end_frame(ram, snapshot);
+	}
+
+	// Runs a single instruction, starting a new frame at a frame boundary and finishing it after the last one.
+	pub fn step_instruction(
+		&mut self,
+		ram: &mut Components::RAM,
+		keyboard: &Components::Keyboard,
+		snapshot: &mut Components::FrameSnapshot
+	) -> MemoryWrite
+	{
+		if self.position == 0
+		{
+			self.begin_frame(ram, keyboard);
+		}
+
+		let pc = self.pc;
+		let (source, destination, next) = ram.read_instruction(pc);
+		let old_value = ram.read_byte(destination);
+		let new_value = ram.read_byte(source);
+
+		ram.write_byte(destination, new_value);
+		self.pc = next;
+		self.position += 1;
+
+		if self.position == INSTRUCTIONS_PER_FRAME
+		{
+			self.end_frame(ram, snapshot);
+		}
+
+		return MemoryWrite {
+			pc,
+			source,
+			destination,
+			old_value,
+			new_value
+		};
+	}
+
+	fn begin_frame(&mut self, ram: &mut Components::RAM, keyboard: &Components::Keyboard)
+	{
+		// The keys go to addresses 0 and 1 as a big-endian bitmap before anything runs.
+		let keys = keyboard.get_state().to_be_bytes();
+		ram.write_byte(0, keys[0]);
+		ram.write_byte(1, keys[1]);
+
 		self.pc = ram.read_triple_byte(2);
+	}
+
+	#[inline]
+	fn end_frame(&mut self, ram: &Components::RAM, snapshot: &mut Components::FrameSnapshot)
+	{
+		snapshot.capture(ram);
+		self.position = 0;
+	}
 
-		for _ in 0 .. 65536
+	// The fast path, without looking at what the instructions do.
+	#[inline]
+	fn run_instructions(&mut self, ram: &mut Components::RAM, count: usize)
+	{
+		let memory = ram.get_memory_mut();
+		let mut pc = self.pc as usize;
+
+		for _ in 0 .. count
 		{
-			ram.write_byte(
-				ram.read_triple_byte(self.pc + 3),
-				ram.read_byte(ram.read_triple_byte(self.pc))
-			);
-			self.pc = ram.read_triple_byte(self.pc + 6);
+			// Source, destination and the next instruction, 3 bytes each. The padding
+			// makes the 9 bytes fit even at the top of memory, so nothing here can go out of bounds.
+			let pc_masked = pc & ADDRESS_MASK;
+			let instruction = &memory[pc_masked .. pc_masked + 9];
+			let source = read_address(&instruction[0 .. 3]);
+			let destination = read_address(&instruction[3 .. 6]);
+			pc = read_address(&instruction[6 .. 9]);
+
+			memory[destination] = memory[source];
 		}
+
+		self.pc = pc as u32;
+		self.position += count;
 	}
 
 	pub fn update(
 		&mut self,
 		ram: &mut Components::RAM,
-		_keyboard: &mut Components::Keyboard,
+		keyboard: &mut Components::Keyboard,
+		snapshot: &mut Components::FrameSnapshot,
 		delta: f64
 	)
 	{
@@ -52,7 +158,7 @@ impl CPU
 		// We are ready to execute the opcode.
 		for _ in 0 .. self.timer.get_ratio()
 		{
-			self.step(ram);
+			self.step(ram, keyboard, snapshot);
 		}
 		self.timer.reset();
 
@@ -41,6 +41,10 @@ impl Keyboard
 		self.keys[index as usize] &= !(1 << bit);
 	}
 
+	// All 16 keys, bit N set while key N is held, the way the machine sees them.
+	#[inline]
+	pub fn get_state(&self) -> u16 { return (self.keys[1] as u16) << 8 | self.keys[0] as u16; }
+
 	#[inline]
 	pub fn halt(&mut self) { self.halting = true; }
 
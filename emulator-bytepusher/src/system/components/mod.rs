@@ -1,8 +1,11 @@
 pub mod cpu;
-pub use cpu::CPU;
+pub use cpu::{MemoryWrite, CPU, INSTRUCTIONS_PER_FRAME};
 
 pub mod ram;
-pub use ram::RAM;
+pub use ram::{default_palette, ADDRESS_MASK, MEMORY_SIZE, RAM};
 
 pub mod keyboard;
 pub use keyboard::Keyboard;
+
+pub mod snapshot;
+pub use snapshot::FrameSnapshot;
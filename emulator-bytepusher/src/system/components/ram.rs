@@ -1,106 +1,148 @@
-use crate::Configs;
+use emulator_common::rgba_bytes;
+
+// Every address is 24 bits.
+pub const MEMORY_SIZE: usize = 0x1000000;
+pub const ADDRESS_MASK: usize = MEMORY_SIZE - 1;
+
+// Zeroes past the end of memory. An instruction at the very top still reads its full 9 bytes,
+// the spec has the missing ones read as padding instead of wrapping around.
+pub const PADDING: usize = 8;
+
+// The whole address space plus padding. Its size is known at compile time and every address is
+// masked to 24 bits, so the compiler can drop the bounds checks from the inner loop.
+pub type Memory = [u8; MEMORY_SIZE + PADDING];
+
+// Big-endian 24 bit address out of the first 3 bytes.
+#[inline(always)]
+pub fn read_address(bytes: &[u8]) -> usize
+{
+	return (bytes[0] as usize) << 16 | (bytes[1] as usize) << 8 | bytes[2] as usize;
+}
+
+// The spec's colours: a 6x6x6 cube for the first 216 entries, black for the rest.
+pub fn default_palette() -> Vec<u32>
+{
+	return (0 .. 256u32)
+		.map(|index| match index
+		{
+			0 ..= 215 => ((index / 36 * 0x33) << 16) | ((index / 6 % 6 * 0x33) << 8) | (index % 6 * 0x33),
+			_ => 0
+		})
+		.collect();
+}
 
 pub struct RAM
 {
-	size: usize,
-	memory: Vec<u8>,
-	colormap: Vec<u32>
+	memory: Box<Memory>,
+	colormap: Vec<u32>,
+	colormap_bytes: Vec<[u8; 4]>
 }
 
 impl RAM
 {
-	pub fn new(config: &Configs::RAMConfig) -> Self
+	pub fn new() -> Self
 	{
-		let mut colormap = vec![0; 256];
-
-		for index in 0 .. 256
-		{
-			colormap[index as usize] = match index
-			{
-				0 ..= 215 =>
-				{
-					let r = (index as u32 / 36) * 0x33;
-					let g = ((index as u32 / 6) % 6) * 0x33;
-					let b = (index as u32 % 6) * 0x33;
-
-					r << 16 | g << 8 | b
-				},
-				_ => 0
-			}
-		}
+		let colormap = default_palette();
 
 		Self {
-			size: config.size,
-			memory: vec![0; config.size],
-			colormap: colormap
+			// Built on the heap, 16 MiB would not fit on the stack.
+			memory: vec![0; MEMORY_SIZE + PADDING]
+				.into_boxed_slice()
+				.try_into()
+				.unwrap(),
+			colormap_bytes: colormap.iter().map(|color| rgba_bytes(*color)).collect(),
+			colormap
 		}
 	}
 
 	#[inline]
 	pub fn get_color_value(&self, index: u8) -> u32 { return self.colormap[index as usize]; }
 
-	#[inline]
-	pub fn read_byte(&self, address: u32) -> u8
+	// Replaces the colour map, missing entries are black.
+	pub fn set_colormap(&mut self, palette: &[u32])
 	{
-		if address >= self.size as u32
+		for index in 0 .. 256
 		{
-			error!(
-				"Attempted to read byte from invalid address: {:#04X}",
-				address
-			);
-			panic!(
-				"Attempted to read byte from invalid address: {:#04X}",
-				address
-			);
+			self.colormap[index] = palette.get(index).copied().unwrap_or(0);
+			self.colormap_bytes[index] = rgba_bytes(self.colormap[index]);
 		}
+	}
+
+	#[inline]
+	pub fn get_colormap(&self) -> &[u32] { return &self.colormap; }
 
-		return self.memory[address as usize];
+	// The colour map as RGBA bytes, ready to be copied into a framebuffer.
+	#[inline]
+	pub fn get_colormap_bytes(&self) -> &[[u8; 4]] { return &self.colormap_bytes; }
+
+	#[inline]
+	pub fn get_size(&self) -> usize { return MEMORY_SIZE; }
+
+	// Addresses wrap at 24 bits like they do on the machine.
+	#[inline]
+	pub fn read_byte(&self, address: u32) -> u8 { return self.memory[address as usize & ADDRESS_MASK]; }
+
+	#[inline]
+	pub fn write_byte(&mut self, address: u32, value: u8) { self.memory[address as usize & ADDRESS_MASK] = value; }
+
+	// The 3 bytes from the address on, reaching into the padding at the top of memory.
+	#[inline]
+	pub fn read_triple_byte(&self, address: u32) -> u32
+	{
+		let address = address as usize & ADDRESS_MASK;
+		return read_address(&self.memory[address .. address + 3]) as u32;
 	}
 
+	// Source, destination and next address of the instruction at the address, padding included.
 	#[inline]
-	pub fn write_byte(&mut self, address: u32, value: u8)
+	pub fn read_instruction(&self, address: u32) -> (u32, u32, u32)
 	{
-		if address >= self.size as u32
-		{
-			error!(
-				"Attempted to write byte to invalid address: {:#04X}",
-				address
-			);
-			panic!(
-				"Attempted to write byte to invalid address: {:#04X}",
-				address
-			);
-		}
+		let address = address as usize & ADDRESS_MASK;
+		let instruction = &self.memory[address .. address + 9];
 
-		self.memory[address as usize] = value;
+		return (
+			read_address(&instruction[0 .. 3]) as u32,
+			read_address(&instruction[3 .. 6]) as u32,
+			read_address(&instruction[6 .. 9]) as u32
+		);
 	}
 
+	// Everything the machine can address, without the padding.
 	#[inline]
-	pub fn read_triple_byte(&self, address: u32) -> u32
+	pub fn get_data(&self) -> &[u8] { return &self.memory[0 .. MEMORY_SIZE]; }
+
+	// The raw padded buffer, for the CPU's inner loop.
+	#[inline]
+	pub fn get_memory_mut(&mut self) -> &mut Memory { return &mut self.memory; }
+
+	pub fn write_data(&mut self, address: usize, data: &[u8])
 	{
-		if address >= (self.size - 2) as u32
+		if address + data.len() > MEMORY_SIZE
 		{
 			error!(
-				"Attempted to read triple byte from invalid address: {:#04X}",
+				"Attempted to write {} bytes to invalid address: {:#04X}",
+				data.len(),
 				address
 			);
 			panic!(
-				"Attempted to read triple byte from invalid address: {:#04X}",
+				"Attempted to write {} bytes to invalid address: {:#04X}",
+				data.len(),
 				address
 			);
 		}
 
-		return (self.memory[address as usize] as u32) << 16
-			| (self.memory[(address + 1) as usize] as u32) << 8
-			| (self.memory[(address + 2) as usize] as u32);
+		self.memory[address .. address + data.len()].copy_from_slice(data);
 	}
 
-	pub fn load_rom_data(&mut self, data: &[u8])
+	pub fn load_rom_data(&mut self, data: &[u8]) -> Result<(), String>
 	{
-		if data.len() > self.size
+		if data.len() > MEMORY_SIZE
 		{
-			error!("ROM file is too big!");
-			panic!("ROM file is too big!");
+			return Err(format!(
+				"ROM file is too big, {} bytes do not fit in {} bytes!",
+				data.len(),
+				MEMORY_SIZE
+			));
 		}
 
 		self.memory[0 .. data.len()].copy_from_slice(data);
@@ -109,5 +151,7 @@ impl RAM
 			"Loaded ROM data consisting of {} bytes into RAM.",
 			data.len()
 		);
+
+		return Ok(());
 	}
 }
@@ -0,0 +1,69 @@
+use emulator_common::SampleBuffer;
+
+use crate::{Components, AUDIO_RATE};
+
+// What the machine shows and plays, captured when a frame ends so frontends never see a half drawn screen.
+pub struct FrameSnapshot
+{
+	pixels: Vec<u8>,
+	audio: SampleBuffer,
+	audio_frame: [i8; 256],
+	frames: u64
+}
+
+impl FrameSnapshot
+{
+	pub fn new() -> Self
+	{
+		Self {
+			pixels: vec![0; 0x10000],
+			audio: SampleBuffer::new(AUDIO_RATE),
+			audio_frame: [0; 256],
+			frames: 0
+		}
+	}
+
+	// Copies the pixel bank the byte at address 5 selects and the audio bank the 2 byte pointer at address 6 points to.
+	pub fn capture(&mut self, ram: &Components::RAM)
+	{
+		let pixel_start = (ram.read_byte(5) as usize) << 16;
+		self.pixels
+			.copy_from_slice(&ram.get_data()[pixel_start .. pixel_start + 0x10000]);
+
+		let audio_start = (ram.read_byte(6) as usize) << 16 | (ram.read_byte(7) as usize) << 8;
+		for (sample, value) in self
+			.audio_frame
+			.iter_mut()
+			.zip(&ram.get_data()[audio_start .. audio_start + 256])
+		{
+			*sample = *value as i8;
+			self.audio.push(*sample as f32 / 128.0);
+		}
+
+		// Nobody is listening, keep only the last second around.
+		if self.audio.len() > AUDIO_RATE as usize * 2
+		{
+			self.audio.keep_newest(AUDIO_RATE as usize);
+		}
+
+		self.frames += 1;
+	}
+
+	// Colour indices of the last finished frame, rows of 256 top to bottom.
+	#[inline]
+	pub fn get_pixels(&self) -> &[u8] { return &self.pixels; }
+
+	#[inline]
+	pub fn get_pixel(&self, x: usize, y: usize) -> u8 { return self.pixels[(y << 8) | x]; }
+
+	// The raw samples of the last finished frame.
+	#[inline]
+	pub fn get_audio_frame(&self) -> &[i8; 256] { return &self.audio_frame; }
+
+	#[inline]
+	pub fn take_audio(&mut self) -> SampleBuffer { return std::mem::replace(&mut self.audio, SampleBuffer::new(AUDIO_RATE)); }
+
+	// Frames finished since power-on.
+	#[inline]
+	pub fn get_frame_count(&self) -> u64 { return self.frames; }
+}
@@ -1,10 +1,14 @@
-use emulator_common::GenericTimerConfig;
+use emulator_common::{color_from_json, GenericTimerConfig};
+
+use crate::Components::default_palette;
 use serde_json::Value;
 
+#[derive(Clone)]
 pub struct EmulatorConfig
 {
-	pub ram_config: RAMConfig,
-	pub cpu_config: CPUConfig
+	pub cpu_config: CPUConfig,
+	// The 0xRRGGBB colour of each of the 256 pixel values.
+	pub palette: Vec<u32>
 }
 
 impl EmulatorConfig
@@ -12,19 +16,35 @@ impl EmulatorConfig
 	pub fn default() -> Self
 	{
 		Self {
-			ram_config: RAMConfig::default(),
-			cpu_config: CPUConfig::default()
+			cpu_config: CPUConfig::default(),
+			palette: default_palette()
 		}
 	}
 
-	pub fn from_json(_data: &Value) -> Self
+	pub fn from_json(data: &Value) -> Self
 	{
-		let result = Self::default();
+		let mut result = Self::default();
+
+		// Change the defaults if they are changed in the config.
+		result.cpu_config.timer.rate = data["frame_rate"]
+			.as_f64()
+			.filter(|rate| *rate > 0.0)
+			.unwrap_or(result.cpu_config.timer.rate);
+
+		// Any of the 256 palette entries can be replaced, in order.
+		if let Some(palette) = data["palette"].as_array()
+		{
+			for (index, color) in palette.iter().take(256).enumerate()
+			{
+				result.palette[index] = color_from_json(color, result.palette[index]);
+			}
+		}
 
 		return result;
 	}
 }
 
+#[derive(Clone)]
 pub struct CPUConfig
 {
 	pub timer: GenericTimerConfig
@@ -39,13 +59,3 @@ impl CPUConfig
 		}
 	}
 }
-
-pub struct RAMConfig
-{
-	pub size: usize
-}
-
-impl RAMConfig
-{
-	pub fn default() -> Self { Self { size: 0x1000000 } }
-}
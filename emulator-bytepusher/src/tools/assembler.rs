@@ -0,0 +1,567 @@
+use std::collections::HashMap;
+
+use crate::Components::{ADDRESS_MASK, MEMORY_SIZE};
+
+// Assembles the ByteByteJump text format into a memory image, loaded at address 0 like any .BytePusher file.
+//
+//     ; Comments run to the end of the line.
+//     org 0
+//     header start, 1, 0x0200     ; keys, reset vector, pixel bank and audio page
+//     org 0x100
+//     start:                      ; labels end with a colon
+//         copy 0x10, 0x20         ; copy a byte, go on with the next instruction
+//         0x10 0x20 start         ; the same as a raw A B C triple, but jumping back
+//         jump start
+//
+// Operands are numbers (decimal, 0x hex or 0b binary), labels, `$` for the address of the
+// current statement, or sums of those without spaces like `table+3` or `$-9`.
+pub fn assemble(source: &str) -> Result<Vec<u8>, String>
+{
+	let statements = parse(source)?;
+
+	// First pass: where everything goes and what the labels point to.
+	let mut labels = HashMap::new();
+	let mut address = 0;
+
+	for statement in &statements
+	{
+		let start = statement
+			.start(address, &labels)
+			.map_err(|error| format!("line {}: {}", statement.line, error))?;
+
+		for label in &statement.labels
+		{
+			if labels.insert(label.clone(), start).is_some()
+			{
+				return Err(format!("line {}: label {} is defined twice", statement.line, label));
+			}
+		}
+
+		address = start
+			+ statement
+				.size(start, &labels)
+				.map_err(|error| format!("line {}: {}", statement.line, error))?;
+	}
+
+	// Second pass: now every label is known, so the bytes can be written.
+	let mut image = Vec::new();
+	let mut address = 0;
+
+	for statement in &statements
+	{
+		let start = statement
+			.start(address, &labels)
+			.map_err(|error| format!("line {}: {}", statement.line, error))?;
+		let bytes = statement
+			.encode(start, &labels)
+			.map_err(|error| format!("line {}: {}", statement.line, error))?;
+
+		if start + bytes.len() > MEMORY_SIZE
+		{
+			return Err(format!("line {}: {:#08X} is past the end of memory", statement.line, start + bytes.len()));
+		}
+
+		if image.len() < start + bytes.len()
+		{
+			image.resize(start + bytes.len(), 0);
+		}
+		image[start .. start + bytes.len()].copy_from_slice(&bytes);
+
+		address = start + bytes.len();
+	}
+
+	return Ok(image);
+}
+
+struct Statement
+{
+	line: usize,
+	labels: Vec<String>,
+	// Lower case mnemonic, empty for a line with nothing but labels, "raw" for a plain A B C triple.
+	mnemonic: String,
+	operands: Vec<String>
+}
+
+fn parse(source: &str) -> Result<Vec<Statement>, String>
+{
+	let mut result = Vec::new();
+
+	for (index, text) in source.lines().enumerate()
+	{
+		let text = text.split(';').next().unwrap_or("");
+		let mut words: Vec<&str> = text
+			.split(|character: char| character.is_whitespace() || character == ',')
+			.filter(|word| !word.is_empty())
+			.collect();
+
+		let mut statement = Statement {
+			line: index + 1,
+			labels: Vec::new(),
+			mnemonic: String::new(),
+			operands: Vec::new()
+		};
+
+		while let Some(label) = words.first().and_then(|word| word.strip_suffix(':'))
+		{
+			if !is_identifier(label)
+			{
+				return Err(format!("line {}: {} is not a valid label", index + 1, label));
+			}
+
+			statement.labels.push(label.to_string());
+			words.remove(0);
+		}
+
+		if let Some(first) = words.first()
+		{
+			let keyword = first.to_lowercase();
+
+			if operand_count(&keyword).is_some()
+			{
+				statement.mnemonic = keyword;
+				words.remove(0);
+			}
+			else
+			{
+				statement.mnemonic = "raw".to_string();
+			}
+
+			statement.operands = words.iter().map(|word| word.to_string()).collect();
+
+			if let Some(count) = operand_count(&statement.mnemonic)
+			{
+				if count != LIST && count != statement.operands.len()
+				{
+					return Err(format!(
+						"line {}: {} takes {} operands, got {}",
+						index + 1,
+						statement.mnemonic,
+						count,
+						statement.operands.len()
+					));
+				}
+			}
+		}
+
+		result.push(statement);
+	}
+
+	return Ok(result);
+}
+
+// Operand count of statements that take a list of any length.
+const LIST: usize = usize::MAX;
+
+// How many operands a mnemonic takes, LIST for lists. None if it isn't one.
+fn operand_count(mnemonic: &str) -> Option<usize>
+{
+	return match mnemonic
+	{
+		"org" | "align" | "jump" => Some(1),
+		"copy" | "fill" => Some(2),
+		"raw" | "header" | "lookup" => Some(3),
+		"add" => Some(4),
+		"add_table" => Some(0),
+		"db" | "dt" => Some(LIST),
+		_ => None
+	};
+}
+
+fn is_identifier(text: &str) -> bool
+{
+	return text
+		.chars()
+		.next()
+		.is_some_and(|character| character.is_ascii_alphabetic() || character == '_')
+		&& text
+			.chars()
+			.all(|character| character.is_ascii_alphanumeric() || character == '_');
+}
+
+impl Statement
+{
+	// Where the statement starts, org and align move the location on.
+	fn start(&self, address: usize, labels: &HashMap<String, usize>) -> Result<usize, String>
+	{
+		return match self.mnemonic.as_str()
+		{
+			"org" => evaluate(&self.operands[0], address, labels),
+			"align" =>
+			{
+				let alignment = evaluate(&self.operands[0], address, labels)?.max(1);
+				Ok(address.div_ceil(alignment) * alignment)
+			},
+			"add_table" =>
+			{
+				if !address.is_multiple_of(0x10000)
+				{
+					return Err(format!("add_table at {:#08X} has to start at a multiple of 0x10000", address));
+				}
+				Ok(address)
+			},
+			_ => Ok(address)
+		};
+	}
+
+	// Bytes the statement takes up. Sizes can't depend on labels defined later.
+	fn size(&self, start: usize, labels: &HashMap<String, usize>) -> Result<usize, String>
+	{
+		return match self.mnemonic.as_str()
+		{
+			"" | "org" | "align" => Ok(0),
+			"raw" | "copy" | "jump" => Ok(9),
+			"lookup" => Ok(18),
+			"add" => Ok(27),
+			"header" => Ok(8),
+			"add_table" => Ok(0x10000),
+			"db" => Ok(self.operands.len()),
+			"dt" => Ok(self.operands.len() * 3),
+			"fill" => evaluate(&self.operands[0], start, labels),
+			mnemonic => Err(format!("unknown statement {}", mnemonic))
+		};
+	}
+
+	fn encode(&self, start: usize, labels: &HashMap<String, usize>) -> Result<Vec<u8>, String>
+	{
+		let operand = |index: usize| -> Result<usize, String> {
+			return evaluate(&self.operands[index], start, labels);
+		};
+
+		let mut result = Vec::new();
+
+		match self.mnemonic.as_str()
+		{
+			"" | "org" | "align" =>
+			{},
+
+			"raw" => push_instruction(&mut result, operand(0)?, operand(1)?, operand(2)?),
+
+			// Copies a byte and goes on with the next instruction.
+			"copy" => push_instruction(&mut result, operand(0)?, operand(1)?, start + 9),
+
+			// Copies the instruction's first byte onto itself, which changes nothing.
+			"jump" => push_instruction(&mut result, start, start, operand(0)?),
+
+			// dst = table[index]. The first instruction patches the index into the low byte of the
+			// second one's source address, so the table has to start at a multiple of 256.
+			"lookup" =>
+			{
+				let (table, index, destination) = (operand(0)?, operand(1)?, operand(2)?);
+				if !table.is_multiple_of(0x100)
+				{
+					return Err(format!("lookup table {:#08X} has to start at a multiple of 0x100", table));
+				}
+
+				push_instruction(&mut result, index, start + 9 + 2, start + 9);
+				push_instruction(&mut result, table, destination, start + 18);
+			},
+
+			// dst = a + b through a 64 KiB add_table, whose middle and low address bytes are patched with a and b.
+			"add" =>
+			{
+				let (table, a, b, destination) = (operand(0)?, operand(1)?, operand(2)?, operand(3)?);
+				if !table.is_multiple_of(0x10000)
+				{
+					return Err(format!("add table {:#08X} has to start at a multiple of 0x10000", table));
+				}
+
+				push_instruction(&mut result, a, start + 18 + 1, start + 9);
+				push_instruction(&mut result, b, start + 18 + 2, start + 18);
+				push_instruction(&mut result, table, destination, start + 27);
+			},
+
+			// Keys, reset vector, pixel bank and the audio page as its upper 2 address bytes.
+			"header" =>
+			{
+				result.extend_from_slice(&[0, 0]);
+				push_address(&mut result, operand(0)?);
+				result.push(operand(1)? as u8);
+				result.extend_from_slice(&(operand(2)? as u16).to_be_bytes());
+			},
+
+			"add_table" =>
+			{
+				for a in 0 .. 256usize
+				{
+					for b in 0 .. 256usize
+					{
+						result.push((a + b) as u8);
+					}
+				}
+			},
+
+			"db" =>
+			{
+				for index in 0 .. self.operands.len()
+				{
+					result.push(operand(index)? as u8);
+				}
+			},
+
+			"dt" =>
+			{
+				for index in 0 .. self.operands.len()
+				{
+					push_address(&mut result, operand(index)?);
+				}
+			},
+
+			"fill" =>
+			{
+				let value = operand(1)? as u8;
+				result.resize(operand(0)?, value);
+			},
+
+			mnemonic => return Err(format!("unknown statement {}", mnemonic))
+		}
+
+		return Ok(result);
+	}
+}
+
+#[inline]
+fn push_address(output: &mut Vec<u8>, address: usize)
+{
+	let address = address & ADDRESS_MASK;
+	output.extend_from_slice(&[(address >> 16) as u8, (address >> 8) as u8, address as u8]);
+}
+
+#[inline]
+fn push_instruction(output: &mut Vec<u8>, source: usize, destination: usize, next: usize)
+{
+	push_address(output, source);
+	push_address(output, destination);
+	push_address(output, next);
+}
+
+// Sums and differences of numbers, labels and $. Negative results wrap, so -1 is 0xFF as a byte,
+// but sums too big for 64 bits are an error.
+fn evaluate(expression: &str, address: usize, labels: &HashMap<String, usize>) -> Result<usize, String>
+{
+	let mut result: i64 = 0;
+	let mut term = String::new();
+	let mut sign = 1;
+
+	for character in expression.chars().chain(std::iter::once('+'))
+	{
+		if (character == '+' || character == '-') && !term.is_empty()
+		{
+			result = evaluate_term(&term, address, labels)?
+				.checked_mul(sign)
+				.and_then(|value| result.checked_add(value))
+				.ok_or(format!("{} overflows", expression))?;
+			term.clear();
+			sign = if character == '-' { -1 } else { 1 };
+		}
+		else if character == '-' && term.is_empty()
+		{
+			sign = -sign;
+		}
+		else if character != '+'
+		{
+			term.push(character);
+		}
+	}
+
+	return Ok(result.rem_euclid(MEMORY_SIZE as i64) as usize);
+}
+
+fn evaluate_term(term: &str, address: usize, labels: &HashMap<String, usize>) -> Result<i64, String>
+{
+	if term == "$"
+	{
+		return Ok(address as i64);
+	}
+
+	let lower = term.to_lowercase();
+	let parsed = if let Some(hex) = lower.strip_prefix("0x")
+	{
+		i64::from_str_radix(hex, 16).ok()
+	}
+	else if let Some(binary) = lower.strip_prefix("0b")
+	{
+		i64::from_str_radix(binary, 2).ok()
+	}
+	else
+	{
+		lower.parse::<i64>().ok()
+	};
+
+	if let Some(value) = parsed
+	{
+		return Ok(value);
+	}
+
+	// Labels can't start with a digit, so this is a number that didn't parse or doesn't fit.
+	if term.starts_with(|character: char| character.is_ascii_digit())
+	{
+		return Err(format!("invalid number {}", term));
+	}
+
+	return match labels.get(term)
+	{
+		Some(value) => Ok(*value as i64),
+		None => Err(format!("unknown label {}", term))
+	};
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use crate::{Configs, Emulator};
+
+	// Loads the image and runs the given number of instructions from the reset vector.
+	fn run(image: &[u8], instructions: usize) -> Emulator
+	{
+		let mut emulator = Emulator::new(&Configs::EmulatorConfig::default());
+		emulator.load_data(image).unwrap();
+
+		for _ in 0 .. instructions
+		{
+			emulator.step_instruction();
+		}
+
+		return emulator;
+	}
+
+	#[test]
+	fn copy_jump_and_raw_triples()
+	{
+		let image = assemble("org 0x20\ncopy 1, 2\njump 0x20\n3 4 0x050607").unwrap();
+
+		assert_eq!(image.len(), 0x20 + 27);
+		assert_eq!(&image[0x20 .. 0x29], &[0, 0, 1, 0, 0, 2, 0, 0, 0x29]);
+		assert_eq!(&image[0x29 .. 0x32], &[0, 0, 0x29, 0, 0, 0x29, 0, 0, 0x20]);
+		assert_eq!(&image[0x32 .. 0x3B], &[0, 0, 3, 0, 0, 4, 5, 6, 7]);
+	}
+
+	#[test]
+	fn labels_and_dollar_resolve()
+	{
+		let image = assemble("org 0x10\nhere: dt here, $, there+1, $-3\nthere: db there").unwrap();
+
+		assert_eq!(
+			&image[0x10 ..],
+			&[0, 0, 0x10, 0, 0, 0x10, 0, 0, 0x1D, 0, 0, 0x0D, 0x1C]
+		);
+
+		assert_eq!(assemble("db nowhere").unwrap_err(), "line 1: unknown label nowhere");
+		assert_eq!(assemble("a: db 0\na: db 1").unwrap_err(), "line 2: label a is defined twice");
+		assert!(assemble("1a: db 0").is_err());
+	}
+
+	#[test]
+	fn header_fills_the_first_8_bytes()
+	{
+		let image = assemble("header 0x123456, 7, 0x0809").unwrap();
+
+		assert_eq!(image, vec![0, 0, 0x12, 0x34, 0x56, 7, 8, 9]);
+	}
+
+	#[test]
+	fn align_moves_to_the_next_multiple()
+	{
+		// Aligning an address that already is aligned leaves it alone.
+		let image = assemble("db 1\nalign 0x100\nhere: dt here\nalign 0x100\nalign 0x100\ndb 3").unwrap();
+		assert_eq!(image.len(), 0x201);
+		assert_eq!(&image[0x100 .. 0x103], &[0, 1, 0]);
+		assert_eq!(image[0x200], 3);
+	}
+
+	#[test]
+	fn add_table_has_every_sum()
+	{
+		let image = assemble("org 0x10000\nadd_table").unwrap();
+
+		assert_eq!(image.len(), 0x20000);
+		for (a, b) in [(0usize, 0usize), (1, 2), (200, 100), (255, 255)]
+		{
+			assert_eq!(image[0x10000 + a * 256 + b], (a + b) as u8);
+		}
+	}
+
+	#[test]
+	fn tables_have_to_be_aligned()
+	{
+		let error = assemble("db 0\nadd_table").unwrap_err();
+		assert!(error.contains("multiple of 0x10000"), "{}", error);
+
+		let error = assemble("lookup 0x180, 0, 0").unwrap_err();
+		assert!(error.starts_with("line 1:") && error.contains("multiple of 0x100"), "{}", error);
+
+		let error = assemble("\nadd 0x100, 0, 0, 0").unwrap_err();
+		assert!(error.starts_with("line 2:") && error.contains("multiple of 0x10000"), "{}", error);
+	}
+
+	#[test]
+	fn lookup_reads_the_indexed_table_entry()
+	{
+		let source = "
+			header start, 0, 0
+			org 0x100
+			start:
+				lookup table, index, result
+				jump $
+			index: db 3
+			result: db 0
+			align 0x100
+			table: db 10, 20, 30, 40, 50
+		";
+		let image = assemble(source).unwrap();
+
+		let emulator = run(&image, 2);
+		assert_eq!(emulator.get_memory()[0x100 + 27 + 1], 40);
+		assert_eq!(emulator.get_pc(), 0x100 + 18);
+	}
+
+	#[test]
+	fn add_sums_through_the_table()
+	{
+		let source = "
+			header start, 0, 0
+			org 0x100
+			start:
+				add table, a, b, result
+				jump $
+			a: db 200
+			b: db 100
+			result: db 0
+			org 0x10000
+			table: add_table
+		";
+		let image = assemble(source).unwrap();
+
+		let emulator = run(&image, 3);
+		assert_eq!(emulator.get_memory()[0x100 + 36 + 2], 44);
+		assert_eq!(emulator.get_pc(), 0x100 + 27);
+
+		// Running it again patches the table address afresh, so it works every frame.
+		let image = assemble(&source.replace("db 200", "db 1").replace("db 100", "db 2")).unwrap();
+		let emulator = run(&image, 3);
+		assert_eq!(emulator.get_memory()[0x100 + 36 + 2], 3);
+	}
+
+	#[test]
+	fn negative_values_wrap()
+	{
+		assert_eq!(assemble("db -1, 2-3").unwrap(), vec![0xFF, 0xFF]);
+	}
+
+	#[test]
+	fn overflowing_sums_are_errors()
+	{
+		let error = assemble("db 0\ndb 0x7FFFFFFFFFFFFFFF+1").unwrap_err();
+		assert!(error.starts_with("line 2:"), "{}", error);
+		assert!(error.contains("overflows"), "{}", error);
+
+		assert!(assemble("db -0x7FFFFFFFFFFFFFFF-2").is_err());
+	}
+
+	#[test]
+	fn numbers_too_big_for_64_bits_are_errors()
+	{
+		let error = assemble("\n\ndb 99999999999999999999").unwrap_err();
+		assert_eq!(error, "line 3: invalid number 99999999999999999999");
+	}
+}
@@ -0,0 +1,345 @@
+use std::collections::BTreeSet;
+
+use crate::Components::{MemoryWrite, INSTRUCTIONS_PER_FRAME, MEMORY_SIZE};
+use crate::Emulator;
+
+// Bytes the hex view shows at once, 16 rows of 16.
+pub const HEX_PAGE_SIZE: usize = 256;
+const HEX_ROW_SIZE: usize = 16;
+
+// Why the debugger handed control back.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StopReason
+{
+	// Ran every instruction it was asked to.
+	Stepped,
+	FrameEnd,
+	// PC reached a breakpoint, the instruction there has not run yet.
+	Breakpoint(u32),
+	// An instruction wrote into a watched range.
+	Watchpoint(MemoryWrite)
+}
+
+// Breakpoints, watched address ranges and whether frame boundaries stop the program.
+pub struct Debugger
+{
+	breakpoints: BTreeSet<u32>,
+	watches: Vec<(u32, u32)>,
+	break_on_frame: bool
+}
+
+impl Debugger
+{
+	pub fn new() -> Self
+	{
+		Self {
+			breakpoints: BTreeSet::new(),
+			watches: Vec::new(),
+			break_on_frame: false
+		}
+	}
+
+	// Sets a breakpoint or removes the one already there, returns whether it is set now.
+	pub fn toggle_breakpoint(&mut self, address: u32) -> bool
+	{
+		if self.breakpoints.remove(&address)
+		{
+			return false;
+		}
+
+		self.breakpoints.insert(address);
+		return true;
+	}
+
+	#[inline]
+	pub fn get_breakpoints(&self) -> &BTreeSet<u32> { return &self.breakpoints; }
+
+	// Stops on any write from start to end, both included.
+	#[inline]
+	pub fn add_watch(&mut self, start: u32, end: u32) { self.watches.push((start.min(end), start.max(end))); }
+
+	#[inline]
+	pub fn clear_watches(&mut self) { self.watches.clear(); }
+
+	#[inline]
+	pub fn get_watches(&self) -> &[(u32, u32)] { return &self.watches; }
+
+	#[inline]
+	pub fn set_break_on_frame(&mut self, value: bool) { self.break_on_frame = value; }
+
+	#[inline]
+	pub fn get_break_on_frame(&self) -> bool { return self.break_on_frame; }
+
+	#[inline]
+	fn is_watched(&self, address: u32) -> bool
+	{
+		return self
+			.watches
+			.iter()
+			.any(|(start, end)| *start <= address && address <= *end);
+	}
+
+	// Runs up to count instructions one at a time. A breakpoint on the instruction it starts at
+	// doesn't stop it, so running again goes on past the breakpoint it stopped at.
+	pub fn run(&self, emulator: &mut Emulator, count: usize) -> StopReason
+	{
+		for index in 0 .. count
+		{
+			let pc = emulator.get_pc();
+			if index > 0 && self.breakpoints.contains(&pc)
+			{
+				return StopReason::Breakpoint(pc);
+			}
+
+			let write = emulator.step_instruction();
+
+			if self.is_watched(write.destination)
+			{
+				return StopReason::Watchpoint(write);
+			}
+
+			if self.break_on_frame && emulator.get_frame_position() == 0
+			{
+				return StopReason::FrameEnd;
+			}
+		}
+
+		return StopReason::Stepped;
+	}
+
+	// Finishes the current frame, or runs a whole one at a frame boundary. Breakpoints and watches still stop it.
+	pub fn run_to_frame_end(&self, emulator: &mut Emulator) -> StopReason
+	{
+		let count = INSTRUCTIONS_PER_FRAME - emulator.get_frame_position();
+
+		return match self.run(emulator, count)
+		{
+			StopReason::Stepped => StopReason::FrameEnd,
+			reason => reason
+		};
+	}
+}
+
+// A named part of memory, end excluded.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct MemoryRegion
+{
+	pub start: usize,
+	pub end: usize,
+	pub name: &'static str
+}
+
+// The fixed header fields, the pixel and audio banks they currently select and the next instruction.
+pub fn memory_regions(emulator: &Emulator) -> Vec<MemoryRegion>
+{
+	let memory = emulator.get_memory();
+	let pixels = (memory[5] as usize) << 16;
+	let audio = (memory[6] as usize) << 16 | (memory[7] as usize) << 8;
+	let pc = emulator.get_pc() as usize;
+
+	let region = |start: usize, end: usize, name: &'static str| -> MemoryRegion {
+		return MemoryRegion { start, end, name };
+	};
+
+	return vec![
+		region(0, 2, "keys"),
+		region(2, 5, "reset vector"),
+		region(5, 6, "pixel bank"),
+		region(6, 8, "audio bank"),
+		region(pixels, pixels + 0x10000, "pixels"),
+		region(audio, audio + 256, "audio"),
+		region(pc, pc + 9, "pc"),
+	];
+}
+
+// Hex and ASCII dump of one HEX_PAGE_SIZE page, every row followed by the regions it touches.
+pub fn hex_page(emulator: &Emulator, page: usize) -> String
+{
+	let memory = emulator.get_memory();
+	let regions = memory_regions(emulator);
+	let page_count = MEMORY_SIZE / HEX_PAGE_SIZE;
+	let page = page % page_count;
+
+	let mut result = format!("page {:#06X} of {:#06X}\n", page, page_count - 1);
+
+	for row in (page * HEX_PAGE_SIZE .. (page + 1) * HEX_PAGE_SIZE).step_by(HEX_ROW_SIZE)
+	{
+		let bytes = &memory[row .. row + HEX_ROW_SIZE];
+
+		result.push_str(&format!("{:06X} ", row));
+
+		for byte in bytes
+		{
+			result.push_str(&format!(" {:02X}", byte));
+		}
+
+		result.push_str("  |");
+		for byte in bytes
+		{
+			let character = *byte as char;
+			result.push(if character.is_ascii_graphic() || character == ' ' { character } else { '.' });
+		}
+		result.push('|');
+
+		let names: Vec<&str> = regions
+			.iter()
+			.filter(|region| region.start < row + HEX_ROW_SIZE && row < region.end)
+			.map(|region| region.name)
+			.collect();
+
+		if !names.is_empty()
+		{
+			result.push_str(&format!("  {}", names.join(", ")));
+		}
+
+		result.push('\n');
+	}
+
+	return result;
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use crate::Components::MemoryWrite;
+	use crate::{assemble, Configs};
+
+	// Three copies at 0x100, 0x109 and 0x112, then a jump back to the first.
+	const PROGRAM: &str = "
+		header start, 1, 0x0200
+		org 0x10
+		source: db 0x55, 0x66, 0x77
+		org 0x20
+		destination: db 0x11, 0x22, 0x33
+		org 0x100
+		start:
+			copy source, destination
+			copy source+1, destination+1
+			copy source+2, destination+2
+			jump start
+	";
+
+	fn emulator() -> Emulator
+	{
+		let mut result = Emulator::new(&Configs::EmulatorConfig::default());
+		result.load_data(&assemble(PROGRAM).unwrap()).unwrap();
+
+		return result;
+	}
+
+	#[test]
+	fn breakpoint_stops_before_the_instruction()
+	{
+		let mut emulator = emulator();
+		let mut debugger = Debugger::new();
+		assert!(debugger.toggle_breakpoint(0x112));
+
+		assert_eq!(debugger.run(&mut emulator, 100), StopReason::Breakpoint(0x112));
+		assert_eq!(emulator.get_pc(), 0x112);
+		assert_eq!(emulator.get_frame_position(), 2);
+		assert_eq!(&emulator.get_memory()[0x20 .. 0x23], &[0x55, 0x66, 0x33]);
+	}
+
+	#[test]
+	fn breakpoint_at_the_start_is_run_past()
+	{
+		let mut emulator = emulator();
+		let mut debugger = Debugger::new();
+		debugger.toggle_breakpoint(0x112);
+
+		debugger.run(&mut emulator, 100);
+
+		// Starting on the breakpoint runs it, the loop comes back to it 4 instructions later.
+		assert_eq!(debugger.run(&mut emulator, 100), StopReason::Breakpoint(0x112));
+		assert_eq!(emulator.get_frame_position(), 6);
+		assert_eq!(emulator.get_memory()[0x22], 0x77);
+
+		// Removed, it doesn't stop any more.
+		assert!(!debugger.toggle_breakpoint(0x112));
+		assert_eq!(debugger.run(&mut emulator, 100), StopReason::Stepped);
+		assert_eq!(emulator.get_frame_position(), 106);
+	}
+
+	#[test]
+	fn watch_reports_the_write()
+	{
+		let mut emulator = emulator();
+		let mut debugger = Debugger::new();
+		debugger.add_watch(0x22, 0x21);
+
+		let expected = MemoryWrite {
+			pc: 0x109,
+			source: 0x11,
+			destination: 0x21,
+			old_value: 0x22,
+			new_value: 0x66
+		};
+		assert_eq!(debugger.run(&mut emulator, 100), StopReason::Watchpoint(expected));
+		assert_eq!(debugger.get_watches(), &[(0x21, 0x22)]);
+		// The write already happened, the next instruction is up.
+		assert_eq!(emulator.get_pc(), 0x112);
+
+		debugger.clear_watches();
+		assert_eq!(debugger.run(&mut emulator, 100), StopReason::Stepped);
+	}
+
+	#[test]
+	fn frame_boundaries_stop_when_asked()
+	{
+		let mut emulator = emulator();
+		let mut debugger = Debugger::new();
+
+		// Without break_on_frame a run goes straight over the boundary.
+		assert_eq!(debugger.run(&mut emulator, INSTRUCTIONS_PER_FRAME + 5), StopReason::Stepped);
+		assert_eq!(emulator.get_frame_position(), 5);
+
+		debugger.set_break_on_frame(true);
+		assert_eq!(debugger.run(&mut emulator, 2 * INSTRUCTIONS_PER_FRAME), StopReason::FrameEnd);
+		assert_eq!(emulator.get_frame_position(), 0);
+		assert_eq!(emulator.get_snapshot().get_frame_count(), 2);
+		// At the boundary the next instruction is the reset vector.
+		assert_eq!(emulator.get_pc(), 0x100);
+	}
+
+	#[test]
+	fn run_to_frame_end_finishes_the_frame()
+	{
+		let mut emulator = emulator();
+		let debugger = Debugger::new();
+
+		debugger.run(&mut emulator, 7);
+		assert_eq!(debugger.run_to_frame_end(&mut emulator), StopReason::FrameEnd);
+		assert_eq!(emulator.get_frame_position(), 0);
+		assert_eq!(emulator.get_snapshot().get_frame_count(), 1);
+
+		// At a boundary it runs a whole frame.
+		assert_eq!(debugger.run_to_frame_end(&mut emulator), StopReason::FrameEnd);
+		assert_eq!(emulator.get_snapshot().get_frame_count(), 2);
+	}
+
+	#[test]
+	fn hex_pages_name_the_regions()
+	{
+		let emulator = emulator();
+
+		let header = hex_page(&emulator, 0);
+		let first_row = header.lines().nth(1).unwrap();
+		assert!(first_row.starts_with("000000  00 00 00 01 00 01 02 00"), "{}", first_row);
+		for name in ["keys", "reset vector", "pixel bank", "audio bank"]
+		{
+			assert!(first_row.contains(name), "{} is not in {}", name, first_row);
+		}
+		assert!(header.lines().nth(2).unwrap().ends_with("|Ufw.............|"));
+
+		let code = hex_page(&emulator, 1);
+		assert!(code.starts_with("page 0x0001 of 0xFFFF\n"));
+		assert!(code.lines().nth(1).unwrap().ends_with("pc"));
+		assert!(!code.lines().nth(2).unwrap().contains("pc"));
+
+		assert!(hex_page(&emulator, 0x100).lines().nth(1).unwrap().ends_with("pixels"));
+		assert!(hex_page(&emulator, 0x200).lines().nth(1).unwrap().ends_with("audio"));
+		// Pages wrap around the end of memory.
+		assert_eq!(hex_page(&emulator, 0x10000), header);
+	}
+}
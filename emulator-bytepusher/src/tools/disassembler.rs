@@ -0,0 +1,194 @@
+use std::collections::HashSet;
+
+// A single ByteByteJump instruction: copy the byte at source to destination, then go on at next.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Instruction
+{
+	pub address: usize,
+	pub source: usize,
+	pub destination: usize,
+	pub next: usize
+}
+
+// Big-endian 24 bit value, bytes past the end of the image read as 0 like the padding does.
+fn read_triple(memory: &[u8], address: usize) -> usize
+{
+	let byte = |offset: usize| -> usize { return memory.get(address + offset).copied().unwrap_or(0) as usize; };
+
+	return byte(0) << 16 | byte(1) << 8 | byte(2);
+}
+
+impl Instruction
+{
+	pub fn read(memory: &[u8], address: usize) -> Self
+	{
+		Self {
+			address,
+			source: read_triple(memory, address),
+			destination: read_triple(memory, address + 3),
+			next: read_triple(memory, address + 6)
+		}
+	}
+
+	// The instruction in the assembler's syntax, as copy or jump where those assemble to the same bytes.
+	pub fn to_source(&self) -> String
+	{
+		if self.next == self.address + 9
+		{
+			return format!("copy {:#08X}, {:#08X}", self.source, self.destination);
+		}
+
+		if self.source == self.address && self.destination == self.address
+		{
+			return format!("jump {:#08X}", self.next);
+		}
+
+		return format!("{:#08X} {:#08X} {:#08X}", self.source, self.destination, self.next);
+	}
+}
+
+// Follows the program from the reset vector at address 2 until it comes back to an instruction
+// it already listed, or limit instructions. Every line assembles into the bytes it was read from,
+// but instructions that overlap each other or the header get their own org blocks, and bytes off
+// the path are left out, so the listing is for reading rather than for rebuilding the ROM.
+// It is a static view: code that rewrites itself while running takes other paths.
+pub fn disassemble(memory: &[u8], limit: usize) -> String
+{
+	let mut result = String::new();
+
+	let reset = read_triple(memory, 2);
+	let pixels = memory.get(5).copied().unwrap_or(0);
+	let audio = (memory.get(6).copied().unwrap_or(0) as u16) << 8 | memory.get(7).copied().unwrap_or(0) as u16;
+
+	result.push_str("org 0\n");
+	result.push_str(&format!("header {:#08X}, {:#04X}, {:#06X}\n", reset, pixels, audio));
+
+	let mut visited = HashSet::new();
+	let mut expected = None;
+	let mut pc = reset;
+
+	for _ in 0 .. limit
+	{
+		if !visited.insert(pc)
+		{
+			result.push_str(&format!("; continues at {:#08X}, listed above\n", pc));
+			return result;
+		}
+
+		// Only start a new block when the chain doesn't simply run on.
+		if expected != Some(pc)
+		{
+			result.push_str(&format!("\norg {:#08X}\n", pc));
+		}
+
+		let instruction = Instruction::read(memory, pc);
+		result.push_str(&format!("\t{}\n", instruction.to_source()));
+
+		expected = Some(pc + 9);
+		pc = instruction.next;
+	}
+
+	result.push_str(&format!("; stopped after {} instructions\n", limit));
+
+	return result;
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use crate::assemble;
+
+	const PROGRAM: &str = "
+		header start, 1, 0x0200
+		org 0x100
+		start:
+			copy 0x10, 0x20
+			lookup table, 0x30, 0x31
+			0x10 0x20 far
+		org 0x4000
+		far:
+			jump start
+		org 0x5000
+		table: db 1, 2, 3
+	";
+
+	// Addresses of the instructions the program runs through from the reset vector.
+	fn path(image: &[u8]) -> Vec<usize>
+	{
+		let mut result = Vec::new();
+		let mut pc = read_triple(image, 2);
+
+		while !result.contains(&pc)
+		{
+			result.push(pc);
+			pc = Instruction::read(image, pc).next;
+		}
+
+		return result;
+	}
+
+	#[test]
+	fn instructions_read_as_copy_jump_or_triple()
+	{
+		let image = assemble("org 0x20\ncopy 1, 2\njump 0x20\n3 4 0x050607").unwrap();
+
+		assert_eq!(Instruction::read(&image, 0x20).to_source(), "copy 0x000001, 0x000002");
+		assert_eq!(Instruction::read(&image, 0x29).to_source(), "jump 0x000020");
+		assert_eq!(Instruction::read(&image, 0x32).to_source(), "0x000003 0x000004 0x050607");
+	}
+
+	#[test]
+	fn listing_follows_the_program()
+	{
+		let listing = disassemble(&assemble(PROGRAM).unwrap(), 100);
+		let lines: Vec<&str> = listing.lines().map(str::trim).collect();
+
+		assert_eq!(
+			lines,
+			vec![
+				"org 0",
+				"header 0x000100, 0x01, 0x0200",
+				"",
+				"org 0x000100",
+				"copy 0x000010, 0x000020",
+				"copy 0x000030, 0x000114",
+				"copy 0x005000, 0x000031",
+				"0x000010 0x000020 0x004000",
+				"",
+				"org 0x004000",
+				"jump 0x000100",
+				"; continues at 0x000100, listed above"
+			]
+		);
+	}
+
+	#[test]
+	fn endless_chains_stop_at_the_limit()
+	{
+		// Every instruction jumps 9 bytes on, through zeroed memory that never repeats.
+		let image = assemble("header 0x100, 0, 0\norg 0x100\ncopy 0, 0\ncopy 0, 0\ncopy 0, 0").unwrap();
+		let listing = disassemble(&image, 2);
+
+		assert_eq!(listing.matches("copy").count(), 2);
+		assert!(listing.ends_with("; stopped after 2 instructions\n"), "{}", listing);
+	}
+
+	#[test]
+	fn listing_assembles_back_into_the_path()
+	{
+		let image = assemble(PROGRAM).unwrap();
+		let again = assemble(&disassemble(&image, 100)).unwrap();
+
+		assert_eq!(&again[.. 8], &image[.. 8]);
+
+		let path = path(&image);
+		assert_eq!(path, vec![0x100, 0x109, 0x112, 0x11B, 0x4000]);
+		assert_eq!(path, self::path(&again));
+
+		for address in path
+		{
+			assert_eq!(&again[address .. address + 9], &image[address .. address + 9], "at {:#08X}", address);
+		}
+	}
+}
@@ -0,0 +1,8 @@
+pub mod assembler;
+pub use assembler::*;
+
+pub mod disassembler;
+pub use disassembler::*;
+
+pub mod debugger;
+pub use debugger::*;
@@ -1,2 +0,0 @@
-pub mod raylib_frontend;
-pub use raylib_frontend::*;
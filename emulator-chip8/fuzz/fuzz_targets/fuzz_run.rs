@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// Any input has to end as running, halted or a fault. A panic is a bug in the core.
+fuzz_target!(|data: &[u8]| {
+	let _ = emulator_chip8::fuzz_run(data, 100_000);
+});
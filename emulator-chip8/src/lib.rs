@@ -2,16 +2,23 @@ mod system;
 pub use system::components as Components;
 pub use system::configs as Configs;
 
-mod user_interfaces;
-use std::io::Read;
+use std::io::{Error, ErrorKind, Read};
 
-use emulator_common::GenericDownTimer;
-pub use user_interfaces::*;
+use emulator_common::{
+	load_memory_file, rgba_bytes, save_memory_file, Framebuffer, GenericDownTimer, Image, SampleBuffer,
+	System
+};
+
+mod tools;
+pub use tools::*;
 
 extern crate pretty_env_logger;
 #[macro_use]
 extern crate log;
 
+// Length of a 60 Hz frame in seconds, used when running without a frontend.
+const FRAME_TIME: f64 = 1.0 / 60.0;
+
 pub struct Emulator
 {
 	cpu: Components::CPU,
@@ -19,7 +26,16 @@ pub struct Emulator
 	display: Components::Display,
 	keyboard: Components::Keyboard,
 	sound: GenericDownTimer,
-	delta: GenericDownTimer
+	delta: GenericDownTimer,
+	buzzer: Components::Buzzer,
+
+	config: Configs::EmulatorConfig,
+	rom: Vec<u8>,
+
+	// Colours lit and unlit pixels have on the System screen.
+	foreground: u32,
+	background: u32,
+	framebuffer: Framebuffer
 }
 
 impl Emulator
@@ -32,15 +48,69 @@ impl Emulator
 			display: Components::Display::new(&config.display_config),
 			keyboard: Components::Keyboard::new(),
 			sound: GenericDownTimer::new(&config.sound_timer_config),
-			delta: GenericDownTimer::new(&config.delta_timer_config)
+			delta: GenericDownTimer::new(&config.delta_timer_config),
+			buzzer: Components::Buzzer::new(&config.buzzer_config),
+			config: config.clone(),
+			rom: Vec::new(),
+			foreground: 0xFFFFFF,
+			background: 0x000000,
+			framebuffer: Framebuffer::new(
+				config.display_config.width as usize,
+				config.display_config.height as usize
+			)
 		};
 		result.cpu.set_pc(config.ram_config.start as u16);
 		return result;
 	}
 
+	#[inline]
+	pub fn get_pc(&self) -> u16 { return self.cpu.get_pc(); }
+
+	#[inline]
+	pub fn get_index(&self) -> u16 { return self.cpu.get_index(); }
+
+	#[inline]
+	pub fn get_registers(&self) -> &[u8; 16] { return self.cpu.get_registers(); }
+
+	#[inline]
+	pub fn get_stack(&self) -> &[u16] { return self.cpu.get_stack(); }
+
+	// The opcode the next step is going to execute.
+	#[inline]
+	pub fn peek_opcode(&self) -> u16 { return self.ram.read_word(self.cpu.get_pc()); }
+
+	#[inline]
+	pub fn get_memory(&self) -> &[u8] { return self.ram.get_data(); }
+
+	#[inline]
+	pub fn get_display_data(&self) -> &[u8] { return self.display.get_data(); }
+
+	#[inline]
+	pub fn get_quirks(&self) -> &Configs::QuirksConfig { return self.cpu.get_quirks(); }
+
+	#[inline]
+	pub fn get_delta_timer(&self) -> u8 { return self.delta.get() as u8; }
+
 	#[inline]
 	pub fn get_sound_timer(&self) -> u8 { return self.sound.get() as u8; }
 
+	// Whether the buzzer is sounding right now, which the VIP option can make differ from the sound timer.
+	#[inline]
+	pub fn is_sound_playing(&self) -> bool { return self.buzzer.is_audible(self.sound.get()); }
+
+	// PCM generated since the last call, at the buzzer's sample rate.
+	#[inline]
+	pub fn take_audio(&mut self) -> SampleBuffer { return self.buzzer.take_samples(); }
+
+	// Picks the colours frontends see, both as 0xRRGGBB.
+	#[inline]
+	pub fn set_colors(&mut self, foreground: u32, background: u32)
+	{
+		self.foreground = foreground;
+		self.background = background;
+		self.display.set_flag();
+	}
+
 	#[inline]
 	pub fn get_draw_flag(&self) -> bool { self.display.get_flag() }
 
@@ -74,16 +144,54 @@ impl Emulator
 	#[inline]
 	pub fn is_running(&self) -> bool { return !self.cpu.halted(); }
 
+	// Why the CPU stopped, if it stopped on a bad program rather than by halting.
+	#[inline]
+	pub fn get_fault(&self) -> Option<Components::Fault> { return self.cpu.get_fault(); }
+
 	#[inline]
 	pub fn load(&mut self, path: &str)
 	{
-		let mut rom = std::fs::File::open(path).expect("Unable to open ROM for loading!");
+		if let Err(error) = self.try_load(path)
+		{
+			error!("{}", error);
+			panic!("{}", error);
+		}
+	}
 
+	// Like load, but reports unreadable or oversized ROMs instead of panicking.
+	pub fn try_load(&mut self, path: &str) -> Result<(), String>
+	{
 		let mut buffer: Vec<u8> = Vec::new();
-		rom.read_to_end(&mut buffer).unwrap();
+		std::fs::File::open(path)
+			.and_then(|mut rom| rom.read_to_end(&mut buffer))
+			.map_err(|error| format!("Unable to open ROM {} for loading: {}", path, error))?;
 		info!("Read ROM from path: {}", path);
 
-		self.ram.load_rom_data(&mut buffer);
+		return self.load_data(&buffer);
+	}
+
+	pub fn load_data(&mut self, data: &[u8]) -> Result<(), String>
+	{
+		self.ram.load_rom_data(data)?;
+		self.rom = data.to_vec();
+
+		return Ok(());
+	}
+
+	// Back to the power-on state with the last loaded ROM, draw logging and colours stay as they were.
+	pub fn reset(&mut self)
+	{
+		let rom = std::mem::take(&mut self.rom);
+		let draw_logging = self.cpu.is_draw_logging();
+
+		let config = self.config.clone();
+		let (foreground, background) = (self.foreground, self.background);
+		*self = Self::new(&config);
+		self.cpu.set_draw_logging(draw_logging);
+		self.set_colors(foreground, background);
+
+		// The ROM fitted before, so it still does.
+		let _ = self.load_data(&rom);
 	}
 
 	#[inline]
@@ -95,6 +203,180 @@ impl Emulator
 	#[inline]
 	pub fn get_display_pixel(&self, x: u8, y: u8) -> bool { return self.display.get_pixel(x, y); }
 
+	// Writes the whole RAM to disk, as Intel HEX if the path ends in .hex and raw binary otherwise.
+	#[inline]
+	pub fn dump_memory(&self, path: &str) -> std::io::Result<()>
+	{
+		return save_memory_file(path, self.ram.get_data());
+	}
+
+	// Loads a binary or Intel HEX file into RAM at the given address while running.
+	pub fn inject_memory(&mut self, path: &str, address: usize) -> std::io::Result<()>
+	{
+		let segments = load_memory_file(path)?;
+
+		// Validate everything first so a bad file doesn't leave memory half patched.
+		for segment in &segments
+		{
+			// A huge inject address must not wrap around past the check.
+			let end = address
+				.checked_add(segment.offset)
+				.and_then(|start| start.checked_add(segment.data.len()));
+
+			if end.is_none_or(|end| end > self.ram.get_size())
+			{
+				return Err(Error::new(
+					ErrorKind::InvalidInput,
+					format!(
+						"{} bytes at {:#04X} + {:#04X} do not fit in RAM",
+						segment.data.len(),
+						address,
+						segment.offset
+					)
+				));
+			}
+		}
+
+		for segment in &segments
+		{
+			self.ram.write_data(address + segment.offset, &segment.data);
+		}
+
+		info!("Injected {} at address {:#04X}.", path, address);
+
+		return Ok(());
+	}
+
+	// Refreshes the framebuffer from the display in one pass, eight pixels per display byte.
+	pub fn frame(&mut self) -> &Framebuffer
+	{
+		let foreground = rgba_bytes(self.foreground);
+		let background = rgba_bytes(self.background);
+
+		for (pixels, byte) in self
+			.framebuffer
+			.get_data_mut()
+			.chunks_exact_mut(32)
+			.zip(self.display.get_data())
+		{
+			for (bit, pixel) in pixels.chunks_exact_mut(4).enumerate()
+			{
+				if (byte >> (7 - bit)) & 1 == 1
+				{
+					pixel.copy_from_slice(&foreground);
+				}
+				else
+				{
+					pixel.copy_from_slice(&background);
+				}
+			}
+		}
+
+		return &self.framebuffer;
+	}
+
+	// Renders the current screen, one image pixel per CHIP8 pixel.
+	pub fn get_display_image(&self, foreground: u32, background: u32) -> Image
+	{
+		let mut result = Image::new(
+			self.display.get_width() as usize,
+			self.display.get_height() as usize,
+			background
+		);
+
+		for y in 0 .. self.display.get_height()
+		{
+			for x in 0 .. self.display.get_width()
+			{
+				if self.display.get_pixel(x, y)
+				{
+					result.set_pixel(x as usize, y as usize, foreground);
+				}
+			}
+		}
+
+		return result;
+	}
+
+	// Reads whatever text the program left on screen using the given glyphs.
+	#[inline]
+	pub fn read_screen_text(&self, table: &GlyphTable) -> String { return recognize_text(&self.display, table); }
+
+	// Starts or stops recording every executed DXYN.
+	#[inline]
+	pub fn set_draw_logging(&mut self, enabled: bool) { self.cpu.set_draw_logging(enabled); }
+
+	#[inline]
+	pub fn take_draw_log(&mut self) -> Vec<Components::SpriteDraw> { return self.cpu.take_draw_log(); }
+
+	#[inline]
+	pub fn render_memory_sprites(
+		&self,
+		start: usize,
+		length: usize,
+		config: &SpriteSheetConfig
+	) -> Image
+	{
+		return render_memory_range(&self.ram, start, length, config);
+	}
+
+	// Makes CXNN reproducible, used when comparing runs.
+	#[inline]
+	pub fn seed_random(&mut self, seed: u64) { self.cpu.seed_random(seed); }
+
+	// Executes a single instruction, the timers are left untouched.
+	#[inline]
+	pub fn step(&mut self)
+	{
+		if self.cpu.halted()
+		{
+			return;
+		}
+
+		self.cpu.step(
+			&mut self.ram,
+			&mut self.display,
+			&mut self.keyboard,
+			&mut self.delta,
+			&mut self.sound
+		);
+	}
+
+	// Lets the buzzer know about FX18 and renders the given ammount of seconds of sound.
+	fn update_buzzer(&mut self, delta: f64)
+	{
+		if let Some(value) = self.cpu.take_sound_write()
+		{
+			self.buzzer.sound_timer_set(value);
+		}
+
+		self.buzzer.update(delta, self.sound.get());
+	}
+
+	// Ticks the 60 Hz timers once, like a frame boundary does.
+	#[inline]
+	pub fn tick_timers(&mut self)
+	{
+		self.sound.update(FRAME_TIME);
+		self.delta.update(FRAME_TIME);
+		self.cpu.update_vsync(FRAME_TIME);
+	}
+
+	#[inline]
+	pub fn get_instructions_per_frame(&self) -> usize { return self.cpu.get_instructions_per_frame(); }
+
+	// Runs a whole frame as fast as possible, for use without a frontend.
+	pub fn run_frame(&mut self)
+	{
+		for _ in 0 .. self.get_instructions_per_frame()
+		{
+			self.step();
+		}
+
+		self.update_buzzer(FRAME_TIME);
+		self.tick_timers();
+	}
+
 	// Updates the emulator state by the given ammount of seconds.
 	#[inline]
 	pub fn update(&mut self, delta: f64)
@@ -115,5 +397,75 @@ impl Emulator
 			&mut self.sound,
 			delta
 		);
+
+		self.update_buzzer(delta);
+	}
+}
+
+impl System for Emulator
+{
+	fn try_load(&mut self, path: &str) -> Result<(), String> { return Emulator::try_load(self, path); }
+
+	fn load_data(&mut self, data: &[u8]) -> Result<(), String> { return Emulator::load_data(self, data); }
+
+	fn reset(&mut self) { Emulator::reset(self); }
+
+	fn update(&mut self, delta: f64) { Emulator::update(self, delta); }
+
+	fn run_frame(&mut self) { Emulator::run_frame(self); }
+
+	fn get_key_count(&self) -> usize { return 16; }
+
+	fn press_key(&mut self, key: u8) { Emulator::press_key(self, key); }
+
+	fn release_key(&mut self, key: u8) { Emulator::release_key(self, key); }
+
+	fn get_screen_width(&self) -> usize { return self.display.get_width() as usize; }
+
+	fn get_screen_height(&self) -> usize { return self.display.get_height() as usize; }
+
+	fn get_screen_pixel(&self, x: usize, y: usize) -> u32
+	{
+		if x >= self.get_screen_width() || y >= self.get_screen_height() || !self.display.get_pixel(x as u8, y as u8)
+		{
+			return self.background;
+		}
+
+		return self.foreground;
+	}
+
+	fn frame(&mut self) -> &Framebuffer { return Emulator::frame(self); }
+
+	fn get_draw_flag(&self) -> bool { return Emulator::get_draw_flag(self); }
+
+	fn reset_draw_flag(&mut self) { Emulator::reset_draw_flag(self); }
+
+	fn is_sound_playing(&self) -> bool { return Emulator::is_sound_playing(self); }
+
+	fn take_audio(&mut self) -> Option<SampleBuffer> { return Some(Emulator::take_audio(self)); }
+
+	fn is_running(&self) -> bool { return Emulator::is_running(self); }
+
+	fn get_fault(&self) -> Option<String> { return Emulator::get_fault(self).map(|fault| fault.to_string()); }
+
+	fn seed_random(&mut self, seed: u64) { Emulator::seed_random(self, seed); }
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn injecting_at_a_huge_address_is_an_error()
+	{
+		let path = std::env::temp_dir().join(format!("inject-chip8-{}.bin", std::process::id()));
+		std::fs::write(&path, [1, 2, 3]).unwrap();
+
+		let mut emulator = Emulator::new(&Configs::EmulatorConfig::default());
+		let result = emulator.inject_memory(path.to_str().unwrap(), usize::MAX - 1);
+		let _ = std::fs::remove_file(&path);
+
+		assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidInput);
 	}
 }
@@ -0,0 +1,189 @@
+use std::f64::consts::TAU;
+
+use emulator_common::SampleBuffer;
+
+use crate::Configs::{BuzzerConfig, Waveform};
+
+// Turns the sound timer into PCM, one tone for as long as the timer is non-zero.
+pub struct Buzzer
+{
+	config: BuzzerConfig,
+	// Position inside the current period, 0.0 ..= 1.0.
+	phase: f64,
+	// Fraction of a sample left over from the last update, so odd frame lengths don't drift.
+	pending: f64,
+	// Set when the current beep was started with a value the VIP would not sound.
+	silenced: bool,
+	samples: SampleBuffer
+}
+
+impl Buzzer
+{
+	pub fn new(config: &BuzzerConfig) -> Self
+	{
+		Self {
+			config: config.clone(),
+			phase: 0.0,
+			pending: 0.0,
+			silenced: false,
+			samples: SampleBuffer::new(config.sample_rate)
+		}
+	}
+
+	// Called with the value of every FX18.
+	#[inline]
+	pub fn sound_timer_set(&mut self, value: u8) { self.silenced = self.config.vip_minimum && value < 2; }
+
+	#[inline]
+	pub fn is_audible(&self, sound_timer: u64) -> bool { return sound_timer != 0 && !self.silenced; }
+
+	// Generates the given ammount of seconds of output for the current sound timer value.
+	pub fn update(&mut self, delta: f64, sound_timer: u64)
+	{
+		let exact = delta * self.config.sample_rate as f64 + self.pending;
+		let count = exact as usize;
+		self.pending = exact - count as f64;
+
+		if !self.is_audible(sound_timer)
+		{
+			// Every beep starts at the beginning of a period.
+			self.phase = 0.0;
+			for _ in 0 .. count
+			{
+				self.samples.push(0.0);
+			}
+		}
+		else
+		{
+			let step = self.config.frequency / self.config.sample_rate as f64;
+			for _ in 0 .. count
+			{
+				let sample = self.config.volume * self.wave_at(self.phase);
+				self.samples.push(sample as f32);
+				self.phase = (self.phase + step).fract();
+			}
+		}
+
+		// Nobody is listening, keep only the last second around.
+		if self.samples.len() > self.config.sample_rate as usize * 2
+		{
+			self.samples.keep_newest(self.config.sample_rate as usize);
+		}
+	}
+
+	// Samples generated since the last call, at the configured sample rate.
+	#[inline]
+	pub fn take_samples(&mut self) -> SampleBuffer
+	{
+		return std::mem::replace(&mut self.samples, SampleBuffer::new(self.config.sample_rate));
+	}
+
+	fn wave_at(&self, phase: f64) -> f64
+	{
+		return match self.config.waveform
+		{
+			Waveform::Square => if phase < 0.5 { 1.0 } else { -1.0 },
+			Waveform::Pulse => if phase < 0.25 { 1.0 } else { -1.0 },
+			Waveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+			Waveform::Sawtooth => 2.0 * phase - 1.0,
+			Waveform::Sine => (phase * TAU).sin()
+		};
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	// 8 samples per period, so every phase step is exact.
+	fn config(waveform: Waveform) -> BuzzerConfig
+	{
+		let mut result = BuzzerConfig::default();
+		result.sample_rate = 8000;
+		result.frequency = 1000.0;
+		result.volume = 1.0;
+		result.waveform = waveform;
+
+		return result;
+	}
+
+	fn one_period(waveform: Waveform) -> Vec<f32>
+	{
+		let mut buzzer = Buzzer::new(&config(waveform));
+		buzzer.update(8.0 / 8000.0, 1);
+
+		return buzzer.take_samples().get_samples().to_vec();
+	}
+
+	fn assert_close(actual: &[f32], expected: &[f32])
+	{
+		assert_eq!(actual.len(), expected.len());
+		for (a, b) in actual.iter().zip(expected)
+		{
+			assert!((a - b).abs() < 1e-3, "{:?} is not {:?}", actual, expected);
+		}
+	}
+
+	#[test]
+	fn every_waveform_has_its_shape()
+	{
+		let root = 0.5f32.sqrt();
+
+		assert_close(&one_period(Waveform::Square), &[1.0, 1.0, 1.0, 1.0, -1.0, -1.0, -1.0, -1.0]);
+		assert_close(&one_period(Waveform::Pulse), &[1.0, 1.0, -1.0, -1.0, -1.0, -1.0, -1.0, -1.0]);
+		assert_close(&one_period(Waveform::Triangle), &[-1.0, -0.5, 0.0, 0.5, 1.0, 0.5, 0.0, -0.5]);
+		assert_close(&one_period(Waveform::Sawtooth), &[-1.0, -0.75, -0.5, -0.25, 0.0, 0.25, 0.5, 0.75]);
+		assert_close(&one_period(Waveform::Sine), &[0.0, root, 1.0, root, 0.0, -root, -1.0, -root]);
+	}
+
+	#[test]
+	fn silent_timer_gives_silence()
+	{
+		let mut buzzer = Buzzer::new(&config(Waveform::Square));
+		buzzer.update(8.0 / 8000.0, 0);
+
+		assert_close(buzzer.take_samples().get_samples(), &[0.0; 8]);
+	}
+
+	#[test]
+	fn vip_minimum_silences_a_timer_of_one()
+	{
+		let mut buzzer = Buzzer::new(&config(Waveform::Square));
+		let mut vip = config(Waveform::Square);
+		vip.vip_minimum = true;
+		let mut vip_buzzer = Buzzer::new(&vip);
+
+		buzzer.sound_timer_set(1);
+		vip_buzzer.sound_timer_set(1);
+		assert!(buzzer.is_audible(1));
+		assert!(!vip_buzzer.is_audible(1));
+
+		vip_buzzer.update(8.0 / 8000.0, 1);
+		assert_close(vip_buzzer.take_samples().get_samples(), &[0.0; 8]);
+
+		vip_buzzer.sound_timer_set(2);
+		assert!(vip_buzzer.is_audible(2));
+
+		vip_buzzer.update(8.0 / 8000.0, 2);
+		assert_close(&vip_buzzer.take_samples().get_samples()[.. 2], &[1.0, 1.0]);
+	}
+
+	#[test]
+	fn fractional_samples_carry_over()
+	{
+		// 1.5 samples per update come out as 1, 2, 1, 2.
+		let mut config = config(Waveform::Square);
+		config.sample_rate = 6;
+		let mut buzzer = Buzzer::new(&config);
+
+		let counts: Vec<usize> = (0 .. 4)
+			.map(|_| {
+				buzzer.update(0.25, 0);
+				return buzzer.take_samples().len();
+			})
+			.collect();
+
+		assert_eq!(counts, vec![1, 2, 1, 2]);
+	}
+}
@@ -1,9 +1,42 @@
 use emulator_common::{
 	clamp, sleep_seconds_f64, GenericDownTimer, GenericTimer, GenericTimerConfig
 };
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 use crate::{Components, Configs};
 
+// A single executed DXYN, as seen by the CPU at the time of drawing.
+pub struct SpriteDraw
+{
+	pub index: u16,
+	pub x: u8,
+	pub y: u8,
+	pub data: Vec<u8>
+}
+
+// Why the CPU stopped on its own. Any program, however broken, ends in one of these instead of a panic.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Fault
+{
+	UnknownOpcode { pc: u16, opcode: u16 },
+	StackOverflow { pc: u16 },
+	StackUnderflow { pc: u16 }
+}
+
+impl std::fmt::Display for Fault
+{
+	fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result
+	{
+		return match self
+		{
+			Self::UnknownOpcode { pc, opcode } => write!(formatter, "unknown opcode {:04X} at {:#05X}", opcode, pc),
+			Self::StackOverflow { pc } => write!(formatter, "stack overflow at {:#05X}", pc),
+			Self::StackUnderflow { pc } => write!(formatter, "stack underflow at {:#05X}", pc)
+		};
+	}
+}
+
 pub struct CPU
 {
 	pc: u16,
@@ -15,10 +48,21 @@ pub struct CPU
 	stack: [u16; 16],
 
 	halt_flag: bool,
+	fault: Option<Fault>,
 
 	vsync: GenericDownTimer,
 
-	timer: GenericTimer
+	timer: GenericTimer,
+	instructions_per_frame: usize,
+
+	quirks: Configs::QuirksConfig,
+	rng: StdRng,
+
+	draw_logging: bool,
+	draw_log: Vec<SpriteDraw>,
+
+	// Last value FX18 loaded since the emulator looked, the buzzer needs to know about every write.
+	sound_write: Option<u8>
 }
 
 impl CPU
@@ -32,43 +76,111 @@ impl CPU
 			stack_ptr: 0,
 			stack: [0; 16],
 			timer: GenericTimer::new(&config.timer),
+			instructions_per_frame: (config.timer.rate / 60.0).round().max(1.0) as usize,
+			quirks: config.quirks,
+			rng: StdRng::from_entropy(),
 			vsync: GenericDownTimer::new(&GenericTimerConfig { rate: 60.0 }),
-			halt_flag: false
+			halt_flag: false,
+			fault: None,
+			draw_logging: false,
+			draw_log: Vec::new(),
+			sound_write: None
 		}
 	}
 
 	#[inline]
 	pub fn set_pc(&mut self, pc: u16) { self.pc = pc; }
 
+	#[inline]
+	pub fn get_pc(&self) -> u16 { return self.pc; }
+
+	#[inline]
+	pub fn get_index(&self) -> u16 { return self.index; }
+
+	#[inline]
+	pub fn get_registers(&self) -> &[u8; 16] { return &self.reg; }
+
+	#[inline]
+	pub fn get_stack(&self) -> &[u16] { return &self.stack[0 .. self.stack_ptr as usize]; }
+
+	#[inline]
+	pub fn get_quirks(&self) -> &Configs::QuirksConfig { return &self.quirks; }
+
+	#[inline]
+	pub fn get_instructions_per_frame(&self) -> usize { return self.instructions_per_frame; }
+
 	#[inline]
 	pub fn halted(&self) -> bool { return self.halt_flag; }
 
 	#[inline]
-	pub fn push_stack(&mut self, value: u16)
+	pub fn take_sound_write(&mut self) -> Option<u8> { return self.sound_write.take(); }
+
+	#[inline]
+	pub fn get_fault(&self) -> Option<Fault> { return self.fault; }
+
+	// Makes CXNN reproducible, used when comparing runs.
+	#[inline]
+	pub fn seed_random(&mut self, seed: u64) { self.rng = StdRng::seed_from_u64(seed); }
+
+	// Advances the display wait by the given ammount of seconds.
+	#[inline]
+	pub fn update_vsync(&mut self, delta: f64) { self.vsync.update(delta); }
+
+	#[inline]
+	pub fn set_draw_logging(&mut self, enabled: bool) { self.draw_logging = enabled; }
+
+	#[inline]
+	pub fn is_draw_logging(&self) -> bool { return self.draw_logging; }
+
+	#[inline]
+	pub fn take_draw_log(&mut self) -> Vec<SpriteDraw> { return std::mem::take(&mut self.draw_log); }
+
+	#[inline]
+	pub fn push_stack(&mut self, value: u16) -> bool
 	{
 		if self.stack_ptr >= 16
 		{
-			error!("Stack overflow!");
-			panic!("Stack overflow!");
+			return false;
 		}
 
 		self.stack[self.stack_ptr as usize] = value;
 		self.stack_ptr += 1;
+
+		return true;
 	}
 
 	#[inline]
-	pub fn pop_stack(&mut self) -> u16
+	pub fn pop_stack(&mut self) -> Option<u16>
 	{
 		if self.stack_ptr == 0
 		{
-			error!("Stack underflow!");
-			panic!("Stack underflow!");
+			return None;
 		}
 
 		self.stack_ptr -= 1;
-		return self.stack[self.stack_ptr as usize];
+		return Some(self.stack[self.stack_ptr as usize]);
 	}
 
+	// Stops the CPU for good, the faulting instruction stays at PC.
+	fn raise(&mut self, fault: Fault, pc: u16)
+	{
+		warn!("CPU fault: {}, halting...", fault);
+		self.pc = pc;
+		self.fault = Some(fault);
+		self.halt_flag = true;
+	}
+
+	// Moves the PC, wrapping around the end of memory.
+	#[inline]
+	fn jump(&mut self, address: usize, ram: &Components::RAM) { self.pc = ram.wrap_address(address) as u16; }
+
+	#[inline]
+	fn skip(&mut self, ram: &Components::RAM) { self.jump(self.pc as usize + 2, ram); }
+
+	// Makes the current instruction run again on the next step.
+	#[inline]
+	fn rewind(&mut self, ram: &Components::RAM) { self.jump(self.pc as usize + ram.get_size() - 2, ram); }
+
 	#[inline]
 	pub fn step(
 		&mut self,
@@ -79,8 +191,9 @@ impl CPU
 		sound: &mut GenericDownTimer
 	)
 	{
-		let opcode = ram.read_word(self.pc);
-		self.pc += 2;
+		let pc = self.pc;
+		let opcode = ram.read_word(pc);
+		self.skip(ram);
 
 		// Split the opcode into nibbles and do the execution.
 		let nibbles = (
@@ -101,26 +214,36 @@ impl CPU
 			// RET
 			(0x0, 0x0, 0xE, 0xE) =>
 			{
-				self.pc = self.pop_stack();
+				match self.pop_stack()
+				{
+					Some(address) => self.jump(address as usize, ram),
+					None => self.raise(Fault::StackUnderflow { pc }, pc)
+				}
 			},
 
 			// JP NNN
 			(0x1, _, _, _) =>
 			{
-				if self.pc - 2 == opcode & 0xFFF
+				if pc == opcode & 0xFFF
 				{
 					warn!("CPU infinite loop detected, halting...");
 					self.halt_flag = true;
 				}
 
-				self.pc = opcode & 0xFFF;
+				self.jump((opcode & 0xFFF) as usize, ram);
 			},
 
 			// CALL NNN
 			(0x2, _, _, _) =>
 			{
-				self.push_stack(self.pc);
-				self.pc = opcode & 0xFFF;
+				if self.push_stack(self.pc)
+				{
+					self.jump((opcode & 0xFFF) as usize, ram);
+				}
+				else
+				{
+					self.raise(Fault::StackOverflow { pc }, pc);
+				}
 			},
 
 			// SKP, Vx, KK
@@ -128,7 +251,7 @@ impl CPU
 			{
 				if self.reg[nibbles.1 as usize] == (opcode & 0xFF) as u8
 				{
-					self.pc += 2;
+					self.skip(ram);
 				}
 			},
 
@@ -137,7 +260,7 @@ impl CPU
 			{
 				if self.reg[nibbles.1 as usize] != (opcode & 0xFF) as u8
 				{
-					self.pc += 2;
+					self.skip(ram);
 				}
 			},
 
@@ -146,7 +269,7 @@ impl CPU
 			{
 				if self.reg[nibbles.1 as usize] == self.reg[nibbles.2 as usize]
 				{
-					self.pc += 2;
+					self.skip(ram);
 				}
 			},
 
@@ -173,21 +296,30 @@ impl CPU
 			(0x8, _, _, 0x1) =>
 			{
 				self.reg[nibbles.1 as usize] |= self.reg[nibbles.2 as usize];
-				self.reg[0xF] = 0
+				if self.quirks.vf_reset
+				{
+					self.reg[0xF] = 0;
+				}
 			},
 
 			// AND Vx, Vy
 			(0x8, _, _, 0x2) =>
 			{
 				self.reg[nibbles.1 as usize] &= self.reg[nibbles.2 as usize];
-				self.reg[0xF] = 0
+				if self.quirks.vf_reset
+				{
+					self.reg[0xF] = 0;
+				}
 			},
 
 			// XOR Vx, Vy
 			(0x8, _, _, 0x3) =>
 			{
 				self.reg[nibbles.1 as usize] ^= self.reg[nibbles.2 as usize];
-				self.reg[0xF] = 0
+				if self.quirks.vf_reset
+				{
+					self.reg[0xF] = 0;
+				}
 			},
 
 			// ADC, Vx, Vy
@@ -211,9 +343,9 @@ impl CPU
 			// SHR, Vx
 			(0x8, _, _, 0x6) =>
 			{
-				let overflow = self.reg[nibbles.2 as usize] & 0x1;
-				self.reg[nibbles.1 as usize] = self.reg[nibbles.2 as usize] >> 1;
-				self.reg[0xF] = overflow;
+				let source = self.shift_source(nibbles.1, nibbles.2);
+				self.reg[nibbles.1 as usize] = source >> 1;
+				self.reg[0xF] = source & 0x1;
 			},
 
 			// SUBN, Vx, Vy
@@ -228,9 +360,9 @@ impl CPU
 			// SHL, Vx
 			(0x8, _, _, 0xE) =>
 			{
-				let overflow = (self.reg[nibbles.2 as usize] & 0x80) >> 7;
-				self.reg[nibbles.1 as usize] = self.reg[nibbles.2 as usize] << 1;
-				self.reg[0xF] = overflow;
+				let source = self.shift_source(nibbles.1, nibbles.2);
+				self.reg[nibbles.1 as usize] = source << 1;
+				self.reg[0xF] = (source & 0x80) >> 7;
 			},
 
 			// SKNE, Vx, Vy
@@ -238,7 +370,7 @@ impl CPU
 			{
 				if self.reg[nibbles.1 as usize] != self.reg[nibbles.2 as usize]
 				{
-					self.pc += 2;
+					self.skip(ram);
 				}
 			},
 
@@ -251,40 +383,86 @@ impl CPU
 			// JP V0, NNN
 			(0xB, _, _, _) =>
 			{
-				self.pc = (opcode & 0xFFF) + self.reg[0] as u16;
+				let mut offset = self.reg[0];
+				if self.quirks.jumping
+				{
+					offset = self.reg[nibbles.1 as usize];
+				}
+
+				self.jump((opcode & 0xFFF) as usize + offset as usize, ram);
 			},
 
 			// RND Vx, NN
 			(0xC, _, _, _) =>
 			{
-				let random: u8 = rand::random();
+				let random: u8 = self.rng.gen();
 				self.reg[nibbles.1 as usize] = random & (opcode & 0xFF) as u8;
 			},
 
 			// DRW X, Y, n
 			(0xD, _, _, _) =>
 			{
-				if self.vsync.get() != 0
+				if self.quirks.display_wait
 				{
-					self.pc -= 2;
-					return;
+					if self.vsync.get() != 0
+					{
+						self.rewind(ram);
+						return;
+					}
+
+					self.vsync.set(1);
 				}
 
-				self.vsync.set(1);
 				display.set_flag();
 
-				let x = self.reg[nibbles.1 as usize] % 64;
-				let y = self.reg[nibbles.2 as usize] % 32;
+				let width = display.get_width();
+				let height = display.get_height();
+				let x = self.reg[nibbles.1 as usize] % width;
+				let y = self.reg[nibbles.2 as usize] % height;
 				let n = nibbles.3;
 
+				if self.draw_logging
+				{
+					trace!(
+						"DRW I={:#05X} N={} at ({}, {})",
+						self.index,
+						n,
+						x,
+						y
+					);
+
+					self.draw_log.push(SpriteDraw {
+						index: self.index,
+						x,
+						y,
+						data: (0 .. n)
+							.map(|i| ram.read_byte(self.index as usize + i as usize))
+							.collect()
+					});
+				}
+
 				let mut collision = false;
 
 				for i in 0 .. n as u8
 				{
-					let byte = ram.read_byte((self.index + i as u16) as usize);
+					let byte = ram.read_byte(self.index as usize + i as usize);
 					for j in 0 .. 8 as u8
 					{
-						if display.set_pixel(x + j, y + i, (byte >> (7 - j)) & 1 == 1)
+						let mut pixel_x = x as u16 + j as u16;
+						let mut pixel_y = y as u16 + i as u16;
+
+						// Off-screen pixels are dropped by the display when clipping.
+						if !self.quirks.clipping
+						{
+							pixel_x %= width as u16;
+							pixel_y %= height as u16;
+						}
+
+						if display.set_pixel(
+							pixel_x.min(0xFF) as u8,
+							pixel_y.min(0xFF) as u8,
+							(byte >> (7 - j)) & 1 == 1
+						)
 						{
 							collision = true;
 						}
@@ -297,18 +475,18 @@ impl CPU
 			// SKP Vx
 			(0xE, _, 0x9, 0xE) =>
 			{
-				if keyboard.is_pressed(self.reg[nibbles.1 as usize])
+				if keyboard.is_pressed(self.reg[nibbles.1 as usize] & 0xF)
 				{
-					self.pc += 2;
+					self.skip(ram);
 				}
 			},
 
 			// SKNP Vx,
 			(0xE, _, 0xA, 0x1) =>
 			{
-				if !keyboard.is_pressed(self.reg[nibbles.1 as usize])
+				if !keyboard.is_pressed(self.reg[nibbles.1 as usize] & 0xF)
 				{
-					self.pc += 2;
+					self.skip(ram);
 				}
 			},
 
@@ -321,7 +499,7 @@ impl CPU
 			// KEY Vx
 			(0xF, _, 0x0, 0xA) =>
 			{
-				self.pc -= 2;
+				self.rewind(ram);
 
 				for i in 0 .. 16
 				{
@@ -333,10 +511,10 @@ impl CPU
 					}
 				}
 
-				if keyboard.halted() && !keyboard.is_pressed(self.reg[nibbles.1 as usize])
+				if keyboard.halted() && !keyboard.is_pressed(self.reg[nibbles.1 as usize] & 0xF)
 				{
 					keyboard.resume();
-					self.pc += 2;
+					self.skip(ram);
 				}
 			},
 
@@ -350,18 +528,19 @@ impl CPU
 			(0xF, _, 0x1, 0x8) =>
 			{
 				sound.set(self.reg[nibbles.1 as usize] as u64);
+				self.sound_write = Some(self.reg[nibbles.1 as usize]);
 			},
 
 			// ADD I, Vx
 			(0xF, _, 0x1, 0xE) =>
 			{
-				self.index += self.reg[nibbles.1 as usize] as u16;
+				self.index = self.index.wrapping_add(self.reg[nibbles.1 as usize] as u16);
 			},
 
 			// DIG Vx, I
 			(0xF, _, 0x2, 0x9) =>
 			{
-				self.index = self.reg[nibbles.1 as usize] as u16 * 5;
+				self.index = (self.reg[nibbles.1 as usize] & 0xF) as u16 * 5;
 			},
 
 			// LDB, Vx
@@ -370,7 +549,7 @@ impl CPU
 				let mut value = self.reg[nibbles.1 as usize];
 				for i in 0 .. 3
 				{
-					ram.write_byte((self.index + 2 - i) as usize, value % 10);
+					ram.write_byte(self.index as usize + 2 - i, value % 10);
 					value /= 10;
 				}
 			},
@@ -380,8 +559,12 @@ impl CPU
 			{
 				for index in 0 .. nibbles.1 + 1
 				{
-					ram.write_byte(self.index as usize, self.reg[index as usize]);
-					self.index += 1;
+					ram.write_byte(self.index as usize + index as usize, self.reg[index as usize]);
+				}
+
+				if self.quirks.memory
+				{
+					self.index = self.index.wrapping_add(nibbles.1 + 1);
 				}
 			},
 
@@ -390,19 +573,34 @@ impl CPU
 			{
 				for index in 0 .. nibbles.1 + 1
 				{
-					self.reg[index as usize] = ram.read_byte(self.index as usize);
-					self.index += 1;
+					self.reg[index as usize] = ram.read_byte(self.index as usize + index as usize);
+				}
+
+				if self.quirks.memory
+				{
+					self.index = self.index.wrapping_add(nibbles.1 + 1);
 				}
 			},
 
 			_ =>
 			{
-				error!("Unknown opcode: {:04X}", opcode);
-				panic!("Unknown opcode: {:04X}", opcode);
+				self.raise(Fault::UnknownOpcode { pc, opcode }, pc);
 			}
 		}
 	}
 
+	// Picks the register 8XY6 and 8XYE shift, depending on the shifting quirk.
+	#[inline]
+	fn shift_source(&self, x: u16, y: u16) -> u8
+	{
+		if self.quirks.shifting
+		{
+			return self.reg[x as usize];
+		}
+
+		return self.reg[y as usize];
+	}
+
 	pub fn update(
 		&mut self,
 		ram: &mut Components::RAM,
@@ -419,6 +617,11 @@ impl CPU
 		// We are ready to execute the opcode.
 		for _ in 0 .. self.timer.get_ratio()
 		{
+			if self.halt_flag
+			{
+				break;
+			}
+
 			self.step(ram, display, keyboard, delta_timer, sound_timer);
 		}
 		self.timer.reset();
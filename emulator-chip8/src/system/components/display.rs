@@ -70,6 +70,9 @@ impl Display
 		return old_value;
 	}
 
+	#[inline]
+	pub fn get_data(&self) -> &[u8] { return &self.memory; }
+
 	#[inline]
 	pub fn get_width(&self) -> u8 { return self.width; }
 
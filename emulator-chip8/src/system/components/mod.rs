@@ -1,5 +1,5 @@
 pub mod cpu;
-pub use cpu::CPU;
+pub use cpu::{Fault, SpriteDraw, CPU};
 
 pub mod display;
 pub use display::Display;
@@ -9,3 +9,6 @@ pub use ram::RAM;
 
 pub mod keyboard;
 pub use keyboard::Keyboard;
+
+pub mod buzzer;
+pub use buzzer::Buzzer;
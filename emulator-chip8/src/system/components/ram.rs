@@ -1,8 +1,8 @@
 use crate::Configs;
 
-const FONTSET_SIZE: usize = 80;
+pub const FONTSET_SIZE: usize = 80;
 
-const FONTSET: [u8; FONTSET_SIZE] = [
+pub const FONTSET: [u8; FONTSET_SIZE] = [
 	0xF0, 0x90, 0x90, 0x90, 0xF0, // 0
 	0x20, 0x60, 0x20, 0x20, 0x70, // 1
 	0xF0, 0x10, 0xF0, 0x80, 0xF0, // 2
@@ -44,66 +44,69 @@ impl RAM
 	}
 
 	#[inline]
-	pub fn read_byte(&self, address: usize) -> u8
-	{
-		if address >= self.size
-		{
-			error!(
-				"Attempted to read byte from invalid address: {:#04X}",
-				address
-			);
-			panic!(
-				"Attempted to read byte from invalid address: {:#04X}",
-				address
-			);
-		}
+	pub fn get_size(&self) -> usize { return self.size; }
 
-		return self.memory[address];
-	}
+	// Addresses wrap around the end of memory, so no program can read or write outside of it.
+	#[inline]
+	pub fn wrap_address(&self, address: usize) -> usize { return address % self.size; }
+
+	#[inline]
+	pub fn read_byte(&self, address: usize) -> u8 { return self.memory[self.wrap_address(address)]; }
 
 	#[inline]
 	pub fn write_byte(&mut self, address: usize, value: u8)
 	{
-		if address >= self.size
-		{
-			error!(
-				"Attempted to write byte to invalid address: {:#04X}",
-				address
-			);
-			panic!(
-				"Attempted to write byte to invalid address: {:#04X}",
-				address
-			);
-		}
-
+		let address = self.wrap_address(address);
 		self.memory[address] = value;
 	}
 
+	// Reads a big endian word, a word at the last byte continues at the start of memory.
 	#[inline]
 	pub fn read_word(&self, address: u16) -> u16
 	{
-		if address >= (self.size - 1) as u16
+		return (self.read_byte(address as usize) as u16) << 8 | (self.read_byte(address as usize + 1) as u16);
+	}
+
+	#[inline]
+	pub fn get_data(&self) -> &[u8] { return &self.memory; }
+
+	pub fn write_data(&mut self, address: usize, data: &[u8])
+	{
+		if address + data.len() > self.size
 		{
 			error!(
-				"Attempted to read word from invalid address: {:#04X}",
+				"Attempted to write {} bytes to invalid address: {:#04X}",
+				data.len(),
 				address
 			);
 			panic!(
-				"Attempted to read word from invalid address: {:#04X}",
+				"Attempted to write {} bytes to invalid address: {:#04X}",
+				data.len(),
 				address
 			);
 		}
 
-		return (self.memory[address as usize] as u16) << 8
-			| (self.memory[(address + 1) as usize] as u16);
+		self.memory[address .. address + data.len()].copy_from_slice(data);
 	}
 
-	pub fn load_rom_data(&mut self, data: &[u8])
+	pub fn load_rom_data(&mut self, data: &[u8]) -> Result<(), String>
 	{
-		if data.len() > self.size - self.start
+		// A loading address past the end would leave nothing to slice, even for an empty ROM.
+		if self.start > self.size
 		{
-			error!("ROM file is too big!");
-			panic!("ROM file is too big!");
+			return Err(format!(
+				"Loading address {:#04X} is past the end of the {} bytes of RAM!",
+				self.start, self.size
+			));
+		}
+
+		if data.len() > self.size.saturating_sub(self.start)
+		{
+			return Err(format!(
+				"ROM file is too big, {} bytes do not fit in {} bytes!",
+				data.len(),
+				self.size.saturating_sub(self.start)
+			));
 		}
 
 		let start = self.start;
@@ -114,5 +117,35 @@ impl RAM
 			"Loaded ROM data consisting of {} bytes into RAM.",
 			data.len()
 		);
+
+		return Ok(());
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn loading_address_past_the_end_is_an_error()
+	{
+		let mut config = Configs::RAMConfig::default();
+		config.start = 0x5000;
+
+		let mut ram = RAM::new(&config);
+		assert!(ram.load_rom_data(&[]).is_err());
+		assert!(ram.load_rom_data(&[0x00, 0xE0]).is_err());
+	}
+
+	#[test]
+	fn rom_may_fill_memory_up_to_the_last_byte()
+	{
+		let mut config = Configs::RAMConfig::default();
+		config.start = config.size;
+
+		let mut ram = RAM::new(&config);
+		assert!(ram.load_rom_data(&[]).is_ok());
+		assert!(ram.load_rom_data(&[0]).is_err());
 	}
 }
@@ -1,13 +1,15 @@
 use emulator_common::GenericTimerConfig;
 use serde_json::Value;
 
+#[derive(Clone)]
 pub struct EmulatorConfig
 {
 	pub ram_config: RAMConfig,
 	pub cpu_config: CPUConfig,
 	pub display_config: DisplayConfig,
 	pub sound_timer_config: GenericTimerConfig,
-	pub delta_timer_config: GenericTimerConfig
+	pub delta_timer_config: GenericTimerConfig,
+	pub buzzer_config: BuzzerConfig
 }
 
 impl EmulatorConfig
@@ -19,7 +21,8 @@ impl EmulatorConfig
 			cpu_config: CPUConfig::default(),
 			display_config: DisplayConfig::default(),
 			sound_timer_config: GenericTimerConfig { rate: 60.0 },
-			delta_timer_config: GenericTimerConfig { rate: 60.0 }
+			delta_timer_config: GenericTimerConfig { rate: 60.0 },
+			buzzer_config: BuzzerConfig::default()
 		}
 	}
 
@@ -34,14 +37,18 @@ impl EmulatorConfig
 		result.ram_config.start = data["loading_address"]
 			.as_u64()
 			.unwrap_or(result.ram_config.start as u64) as usize;
+		result.cpu_config.quirks = QuirksConfig::from_json(&data["quirks"]);
+		result.buzzer_config = BuzzerConfig::from_json(&data["buzzer"]);
 
 		return result;
 	}
 }
 
+#[derive(Clone)]
 pub struct CPUConfig
 {
-	pub timer: GenericTimerConfig
+	pub timer: GenericTimerConfig,
+	pub quirks: QuirksConfig
 }
 
 impl CPUConfig
@@ -49,11 +56,146 @@ impl CPUConfig
 	pub fn default() -> Self
 	{
 		Self {
-			timer: GenericTimerConfig { rate: 1000.0 }
+			timer: GenericTimerConfig { rate: 1000.0 },
+			quirks: QuirksConfig::default()
 		}
 	}
 }
 
+// Behaviours that differ between CHIP8 interpreters, named after Timendus' quirks test.
+// The defaults match the original COSMAC VIP interpreter.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct QuirksConfig
+{
+	// 8XY1, 8XY2 and 8XY3 reset VF.
+	pub vf_reset: bool,
+	// FX55 and FX65 increment I.
+	pub memory: bool,
+	// DXYN waits for the next frame.
+	pub display_wait: bool,
+	// Sprites are clipped at the screen edges instead of wrapping.
+	pub clipping: bool,
+	// 8XY6 and 8XYE shift VX in place instead of loading VY.
+	pub shifting: bool,
+	// BXNN jumps to XNN + VX instead of NNN + V0.
+	pub jumping: bool
+}
+
+impl QuirksConfig
+{
+	pub fn default() -> Self
+	{
+		Self {
+			vf_reset: true,
+			memory: true,
+			display_wait: true,
+			clipping: true,
+			shifting: false,
+			jumping: false
+		}
+	}
+
+	pub fn from_json(data: &Value) -> Self
+	{
+		let mut result = Self::default();
+
+		// Change the defaults if they are changed in the config.
+		result.vf_reset = data["vf_reset"].as_bool().unwrap_or(result.vf_reset);
+		result.memory = data["memory"].as_bool().unwrap_or(result.memory);
+		result.display_wait = data["display_wait"]
+			.as_bool()
+			.unwrap_or(result.display_wait);
+		result.clipping = data["clipping"].as_bool().unwrap_or(result.clipping);
+		result.shifting = data["shifting"].as_bool().unwrap_or(result.shifting);
+		result.jumping = data["jumping"].as_bool().unwrap_or(result.jumping);
+
+		return result;
+	}
+}
+
+// Shape of the buzzer tone.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Waveform
+{
+	Square,
+	// Square wave with a 25% duty cycle, thinner sounding.
+	Pulse,
+	Triangle,
+	Sawtooth,
+	Sine
+}
+
+impl Waveform
+{
+	pub fn from_name(name: &str) -> Option<Self>
+	{
+		return match name
+		{
+			"square" => Some(Self::Square),
+			"pulse" => Some(Self::Pulse),
+			"triangle" => Some(Self::Triangle),
+			"sawtooth" => Some(Self::Sawtooth),
+			"sine" => Some(Self::Sine),
+			_ => None
+		};
+	}
+}
+
+#[derive(Clone)]
+pub struct BuzzerConfig
+{
+	pub sample_rate: u32,
+	pub frequency: f64,
+	// Peak amplitude, 0.0 ..= 1.0.
+	pub volume: f64,
+	pub waveform: Waveform,
+	// The COSMAC VIP stays silent when FX18 loads a value below 2.
+	pub vip_minimum: bool
+}
+
+impl BuzzerConfig
+{
+	pub fn default() -> Self
+	{
+		Self {
+			sample_rate: 44100,
+			frequency: 440.0,
+			volume: 0.25,
+			waveform: Waveform::Square,
+			vip_minimum: false
+		}
+	}
+
+	pub fn from_json(data: &Value) -> Self
+	{
+		let mut result = Self::default();
+
+		// Change the defaults if they are changed in the config.
+		result.sample_rate = data["sample_rate"]
+			.as_u64()
+			.unwrap_or(result.sample_rate as u64)
+			.max(1) as u32;
+		result.frequency = data["frequency"].as_f64().unwrap_or(result.frequency);
+		result.volume = data["volume"]
+			.as_f64()
+			.unwrap_or(result.volume)
+			.clamp(0.0, 1.0);
+		result.vip_minimum = data["vip_minimum"].as_bool().unwrap_or(result.vip_minimum);
+
+		if let Some(name) = data["waveform"].as_str()
+		{
+			match Waveform::from_name(name)
+			{
+				Some(waveform) => result.waveform = waveform,
+				None => warn!("Unknown buzzer waveform: {}, keeping {:?}.", name, result.waveform),
+			}
+		}
+
+		return result;
+	}
+}
+
+#[derive(Clone)]
 pub struct RAMConfig
 {
 	pub start: usize,
@@ -71,6 +213,7 @@ impl RAMConfig
 	}
 }
 
+#[derive(Clone)]
 pub struct DisplayConfig
 {
 	pub width: u8,
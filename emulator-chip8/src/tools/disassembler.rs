@@ -0,0 +1,82 @@
+// Turns a single opcode into a human readable instruction, unknown opcodes are shown as raw data.
+pub fn disassemble(opcode: u16) -> String
+{
+	return decode(opcode).unwrap_or_else(|| format!("DW {:#06X}", opcode));
+}
+
+#[inline]
+pub fn is_known_opcode(opcode: u16) -> bool { return decode(opcode).is_some(); }
+
+// Quirks from Configs::QuirksConfig that can change the outcome of the given opcode.
+pub fn sensitive_quirks(opcode: u16) -> &'static [&'static str]
+{
+	let nibbles = (
+		(opcode & 0xF000) >> 12,
+		(opcode & 0x00F0) >> 4,
+		(opcode & 0x000F)
+	);
+
+	return match nibbles
+	{
+		(0x8, _, 0x1) | (0x8, _, 0x2) | (0x8, _, 0x3) => &["vf_reset"],
+		(0x8, _, 0x6) | (0x8, _, 0xE) => &["shifting"],
+		(0xB, _, _) => &["jumping"],
+		(0xD, _, _) => &["display_wait", "clipping"],
+		(0xF, 0x5, 0x5) | (0xF, 0x6, 0x5) => &["memory"],
+		_ => &[]
+	};
+}
+
+fn decode(opcode: u16) -> Option<String>
+{
+	let nibbles = (
+		(opcode & 0xF000) >> 12,
+		(opcode & 0x0F00) >> 8,
+		(opcode & 0x00F0) >> 4,
+		(opcode & 0x000F)
+	);
+	let nnn = opcode & 0xFFF;
+	let kk = opcode & 0xFF;
+	let (x, y) = (nibbles.1, nibbles.2);
+
+	let result = match nibbles
+	{
+		(0x0, 0x0, 0xE, 0x0) => "CLS".to_string(),
+		(0x0, 0x0, 0xE, 0xE) => "RET".to_string(),
+		(0x1, _, _, _) => format!("JP {:#05X}", nnn),
+		(0x2, _, _, _) => format!("CALL {:#05X}", nnn),
+		(0x3, _, _, _) => format!("SE V{:X}, {:#04X}", x, kk),
+		(0x4, _, _, _) => format!("SNE V{:X}, {:#04X}", x, kk),
+		(0x5, _, _, 0x0) => format!("SE V{:X}, V{:X}", x, y),
+		(0x6, _, _, _) => format!("LD V{:X}, {:#04X}", x, kk),
+		(0x7, _, _, _) => format!("ADD V{:X}, {:#04X}", x, kk),
+		(0x8, _, _, 0x0) => format!("LD V{:X}, V{:X}", x, y),
+		(0x8, _, _, 0x1) => format!("OR V{:X}, V{:X}", x, y),
+		(0x8, _, _, 0x2) => format!("AND V{:X}, V{:X}", x, y),
+		(0x8, _, _, 0x3) => format!("XOR V{:X}, V{:X}", x, y),
+		(0x8, _, _, 0x4) => format!("ADD V{:X}, V{:X}", x, y),
+		(0x8, _, _, 0x5) => format!("SUB V{:X}, V{:X}", x, y),
+		(0x8, _, _, 0x6) => format!("SHR V{:X}, V{:X}", x, y),
+		(0x8, _, _, 0x7) => format!("SUBN V{:X}, V{:X}", x, y),
+		(0x8, _, _, 0xE) => format!("SHL V{:X}, V{:X}", x, y),
+		(0x9, _, _, 0x0) => format!("SNE V{:X}, V{:X}", x, y),
+		(0xA, _, _, _) => format!("LD I, {:#05X}", nnn),
+		(0xB, _, _, _) => format!("JP V0, {:#05X}", nnn),
+		(0xC, _, _, _) => format!("RND V{:X}, {:#04X}", x, kk),
+		(0xD, _, _, _) => format!("DRW V{:X}, V{:X}, {}", x, y, nibbles.3),
+		(0xE, _, 0x9, 0xE) => format!("SKP V{:X}", x),
+		(0xE, _, 0xA, 0x1) => format!("SKNP V{:X}", x),
+		(0xF, _, 0x0, 0x7) => format!("LD V{:X}, DT", x),
+		(0xF, _, 0x0, 0xA) => format!("LD V{:X}, K", x),
+		(0xF, _, 0x1, 0x5) => format!("LD DT, V{:X}", x),
+		(0xF, _, 0x1, 0x8) => format!("LD ST, V{:X}", x),
+		(0xF, _, 0x1, 0xE) => format!("ADD I, V{:X}", x),
+		(0xF, _, 0x2, 0x9) => format!("LD F, V{:X}", x),
+		(0xF, _, 0x3, 0x3) => format!("LD B, V{:X}", x),
+		(0xF, _, 0x5, 0x5) => format!("LD [I], V{:X}", x),
+		(0xF, _, 0x6, 0x5) => format!("LD V{:X}, [I]", x),
+		_ => return None
+	};
+
+	return Some(result);
+}
@@ -0,0 +1,184 @@
+use crate::{Components, Configs, Emulator};
+
+// How a fuzzed program ended.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FuzzOutcome
+{
+	// Still running once the step budget was used up.
+	Running,
+	// Jumped to itself.
+	Halted,
+	Faulted(Components::Fault)
+}
+
+// Quirk combination picked by the first byte of the input, one bit per quirk.
+fn quirks_from_byte(byte: u8) -> Configs::QuirksConfig
+{
+	return Configs::QuirksConfig {
+		vf_reset: byte & 0x01 != 0,
+		memory: byte & 0x02 != 0,
+		display_wait: byte & 0x04 != 0,
+		clipping: byte & 0x08 != 0,
+		shifting: byte & 0x10 != 0,
+		jumping: byte & 0x20 != 0
+	};
+}
+
+// Entry point for fuzzers. The first byte selects the quirks, the remaining bytes are loaded
+// as the program, cut off at the end of memory, and run for at most max_steps instructions.
+// Keys 0-F are held down on alternating frames so key waits make progress.
+pub fn fuzz_run(data: &[u8], max_steps: usize) -> FuzzOutcome
+{
+	let mut config = Configs::EmulatorConfig::default();
+	config.cpu_config.quirks = quirks_from_byte(data.first().copied().unwrap_or(0));
+
+	let program = data.get(1 ..).unwrap_or(&[]);
+	let space = config.ram_config.size - config.ram_config.start;
+
+	let mut emulator = Emulator::new(&config);
+	emulator.seed_random(0);
+	if emulator.load_data(&program[.. program.len().min(space)]).is_err()
+	{
+		return FuzzOutcome::Running;
+	}
+
+	let mut steps = 0;
+	let mut keys_held = true;
+	while steps < max_steps
+	{
+		for key in 0 .. 16
+		{
+			if keys_held
+			{
+				emulator.press_key(key);
+			}
+			else
+			{
+				emulator.release_key(key);
+			}
+		}
+
+		for _ in 0 .. emulator.get_instructions_per_frame().min(max_steps - steps)
+		{
+			emulator.step();
+			steps += 1;
+		}
+
+		if let Some(fault) = emulator.get_fault()
+		{
+			return FuzzOutcome::Faulted(fault);
+		}
+
+		if !emulator.is_running()
+		{
+			return FuzzOutcome::Halted;
+		}
+
+		emulator.tick_timers();
+		keys_held = !keys_held;
+	}
+
+	return FuzzOutcome::Running;
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	// Loads a program at 0x200 and runs it one instruction at a time until it stops or the budget runs out.
+	fn run_program(program: &[u8], max_steps: usize) -> Emulator
+	{
+		let mut emulator = Emulator::new(&Configs::EmulatorConfig::default());
+		emulator.load_data(program).unwrap();
+
+		for _ in 0 .. max_steps
+		{
+			if !emulator.is_running()
+			{
+				break;
+			}
+			emulator.step();
+		}
+
+		return emulator;
+	}
+
+	#[test]
+	fn jump_to_zero_faults_on_the_font_data()
+	{
+		// JP 0x000 lands on the font, whose first word F090 is no instruction.
+		let outcome = fuzz_run(&[0x00, 0x10, 0x00], 100);
+
+		assert_eq!(outcome, FuzzOutcome::Faulted(Components::Fault::UnknownOpcode { pc: 0x000, opcode: 0xF090 }));
+	}
+
+	#[test]
+	fn bcd_at_the_last_byte_wraps_to_the_start()
+	{
+		// I = 0xFFF, V0 = 254, BCD of V0 at I, then halt.
+		let emulator = run_program(&[0xAF, 0xFF, 0x60, 0xFE, 0xF0, 0x33, 0x12, 0x06], 100);
+
+		assert_eq!(emulator.get_fault(), None);
+		assert_eq!(emulator.get_memory()[0xFFF], 2);
+		assert_eq!(emulator.get_memory()[0x000], 5);
+		assert_eq!(emulator.get_memory()[0x001], 4);
+	}
+
+	#[test]
+	fn pc_past_the_end_of_memory_wraps_to_zero()
+	{
+		// JP 0xFFE, where V0 = 5 is the last instruction in memory. After it PC continues at 0x000.
+		let mut program = vec![0; 0xE00];
+		program[0 .. 2].copy_from_slice(&[0x1F, 0xFE]);
+		program[0xDFE .. 0xE00].copy_from_slice(&[0x60, 0x05]);
+
+		let emulator = run_program(&program, 100);
+
+		assert_eq!(emulator.get_registers()[0], 5);
+		assert_eq!(emulator.get_fault(), Some(Components::Fault::UnknownOpcode { pc: 0x000, opcode: 0xF090 }));
+	}
+
+	#[test]
+	fn word_at_the_last_byte_wraps_to_the_start()
+	{
+		let mut ram = Components::RAM::new(&Configs::RAMConfig::default());
+		ram.write_byte(0xFFF, 0x60);
+
+		// The second byte is the first byte of the font.
+		assert_eq!(ram.read_word(0xFFF), 0x60F0);
+
+		// Executed, that is LD V0, 0xF0, with PC continuing at 0x001.
+		let mut program = vec![0; 0xE00];
+		program[0 .. 2].copy_from_slice(&[0x1F, 0xFF]);
+		program[0xDFF] = 0x60;
+
+		let mut emulator = Emulator::new(&Configs::EmulatorConfig::default());
+		emulator.load_data(&program).unwrap();
+		emulator.step();
+		emulator.step();
+
+		assert_eq!(emulator.get_registers()[0], 0xF0);
+		assert_eq!(emulator.get_pc(), 0x001);
+	}
+
+	#[test]
+	fn random_programs_never_panic()
+	{
+		// A fixed xorshift stream, so failures reproduce.
+		let mut state: u64 = 0x9E3779B97F4A7C15;
+		for _ in 0 .. 200
+		{
+			let data: Vec<u8> = (0 .. 512)
+				.map(|_| {
+					state ^= state << 13;
+					state ^= state >> 7;
+					state ^= state << 17;
+					return state as u8;
+				})
+				.collect();
+
+			let _ = fuzz_run(&data, 5000);
+		}
+	}
+}
@@ -0,0 +1,302 @@
+use crate::{disassemble, Emulator};
+
+// How many differing RAM addresses are listed before the rest is summarized.
+const MAX_LISTED_ADDRESSES: usize = 8;
+
+// The first point where both emulators stopped agreeing.
+pub struct Divergence
+{
+	pub step: u64,
+	pub frame: u64,
+	pub pc: u16,
+	pub opcode: u16,
+	pub differences: Vec<String>
+}
+
+// Runs two emulators instruction by instruction on the same inputs.
+pub struct Lockstep
+{
+	left: Emulator,
+	right: Emulator,
+	steps: u64,
+	frames: u64
+}
+
+impl Lockstep
+{
+	pub fn new(left: Emulator, right: Emulator) -> Self
+	{
+		if left.get_instructions_per_frame() != right.get_instructions_per_frame()
+		{
+			warn!("Lockstep emulators have different instruction rates, using the left one.");
+		}
+
+		Self {
+			left,
+			right,
+			steps: 0,
+			frames: 0
+		}
+	}
+
+	#[inline]
+	pub fn get_left(&self) -> &Emulator { return &self.left; }
+
+	#[inline]
+	pub fn get_right(&self) -> &Emulator { return &self.right; }
+
+	#[inline]
+	pub fn get_steps(&self) -> u64 { return self.steps; }
+
+	#[inline]
+	pub fn get_frames(&self) -> u64 { return self.frames; }
+
+	#[inline]
+	pub fn is_running(&self) -> bool { return self.left.is_running() || self.right.is_running(); }
+
+	#[inline]
+	pub fn press_key(&mut self, key: u8)
+	{
+		self.left.press_key(key);
+		self.right.press_key(key);
+	}
+
+	#[inline]
+	pub fn release_key(&mut self, key: u8)
+	{
+		self.left.release_key(key);
+		self.right.release_key(key);
+	}
+
+	// Runs a frame worth of instructions on both sides, stopping at the first divergence.
+	pub fn run_frame(&mut self) -> Option<Divergence>
+	{
+		for _ in 0 .. self.left.get_instructions_per_frame()
+		{
+			let pc = self.left.get_pc();
+			let opcode = self.left.peek_opcode();
+
+			self.left.step();
+			self.right.step();
+			self.steps += 1;
+
+			let differences = compare_states(&self.left, &self.right);
+			if !differences.is_empty()
+			{
+				return Some(Divergence {
+					step: self.steps,
+					frame: self.frames,
+					pc,
+					opcode,
+					differences
+				});
+			}
+		}
+
+		self.left.tick_timers();
+		self.right.tick_timers();
+		self.frames += 1;
+
+		return None;
+	}
+
+	pub fn report(&self, divergence: &Divergence) -> String
+	{
+		let mut result = format!(
+			"Divergence at step {} (frame {}) after {:#05X}: {:04X} {}\n",
+			divergence.step,
+			divergence.frame,
+			divergence.pc,
+			divergence.opcode,
+			disassemble(divergence.opcode)
+		);
+
+		result.push_str("\nDifferences:\n");
+		for difference in &divergence.differences
+		{
+			result.push_str(&format!("  {}\n", difference));
+		}
+
+		result.push_str("\nState:\n");
+		let left = describe_state(&self.left);
+		let right = describe_state(&self.right);
+		let width = left.iter().map(|line| line.len()).max().unwrap_or(0);
+
+		result.push_str(&format!("  {:width$} | {}\n", "left", "right", width = width));
+		for (left_line, right_line) in left.iter().zip(right.iter())
+		{
+			result.push_str(&format!(
+				"  {:width$} | {}\n",
+				left_line,
+				right_line,
+				width = width
+			));
+		}
+
+		return result;
+	}
+}
+
+pub fn compare_states(left: &Emulator, right: &Emulator) -> Vec<String>
+{
+	let mut result = Vec::new();
+
+	if left.get_pc() != right.get_pc()
+	{
+		result.push(format!(
+			"PC: {:#05X} vs {:#05X}",
+			left.get_pc(),
+			right.get_pc()
+		));
+	}
+
+	if left.get_index() != right.get_index()
+	{
+		result.push(format!(
+			"I: {:#05X} vs {:#05X}",
+			left.get_index(),
+			right.get_index()
+		));
+	}
+
+	for index in 0 .. 16
+	{
+		let (left_value, right_value) = (left.get_registers()[index], right.get_registers()[index]);
+		if left_value != right_value
+		{
+			result.push(format!(
+				"V{:X}: {:#04X} vs {:#04X}",
+				index, left_value, right_value
+			));
+		}
+	}
+
+	if left.get_stack() != right.get_stack()
+	{
+		result.push(format!(
+			"Stack: {:X?} vs {:X?}",
+			left.get_stack(),
+			right.get_stack()
+		));
+	}
+
+	if left.get_delta_timer() != right.get_delta_timer()
+	{
+		result.push(format!(
+			"DT: {} vs {}",
+			left.get_delta_timer(),
+			right.get_delta_timer()
+		));
+	}
+
+	if left.get_sound_timer() != right.get_sound_timer()
+	{
+		result.push(format!(
+			"ST: {} vs {}",
+			left.get_sound_timer(),
+			right.get_sound_timer()
+		));
+	}
+
+	if left.is_running() != right.is_running()
+	{
+		result.push(format!(
+			"Running: {} vs {}",
+			left.is_running(),
+			right.is_running()
+		));
+	}
+
+	if left.get_fault() != right.get_fault()
+	{
+		result.push(format!(
+			"Fault: {:?} vs {:?}",
+			left.get_fault(),
+			right.get_fault()
+		));
+	}
+
+	// Memory, listing the first few differing addresses.
+	let differing: Vec<usize> = (0 .. left.get_memory().len().min(right.get_memory().len()))
+		.filter(|address| left.get_memory()[*address] != right.get_memory()[*address])
+		.collect();
+	for address in differing.iter().take(MAX_LISTED_ADDRESSES)
+	{
+		result.push(format!(
+			"RAM[{:#05X}]: {:#04X} vs {:#04X}",
+			address,
+			left.get_memory()[*address],
+			right.get_memory()[*address]
+		));
+	}
+	if differing.len() > MAX_LISTED_ADDRESSES
+	{
+		result.push(format!(
+			"... and {} more RAM addresses",
+			differing.len() - MAX_LISTED_ADDRESSES
+		));
+	}
+
+	// Display, only the amount of pixels matters here, the report shows both screens.
+	let pixels: u32 = left
+		.get_display_data()
+		.iter()
+		.zip(right.get_display_data().iter())
+		.map(|(left_byte, right_byte)| (left_byte ^ right_byte).count_ones())
+		.sum();
+	if pixels > 0
+	{
+		result.push(format!("Display: {} pixels differ", pixels));
+	}
+
+	return result;
+}
+
+// Registers followed by an ASCII picture of the screen, one entry per line.
+pub fn describe_state(emulator: &Emulator) -> Vec<String>
+{
+	let registers = emulator.get_registers();
+	let mut result = vec![
+		format!(
+			"PC={:#05X} I={:#05X} DT={:3} ST={:3}",
+			emulator.get_pc(),
+			emulator.get_index(),
+			emulator.get_delta_timer(),
+			emulator.get_sound_timer()
+		),
+		format!("Stack={:X?}", emulator.get_stack()),
+	];
+
+	for row in 0 .. 4
+	{
+		result.push(
+			(0 .. 4)
+				.map(|column| {
+					let index = row * 4 + column;
+					format!("V{:X}={:#04X}", index, registers[index])
+				})
+				.collect::<Vec<String>>()
+				.join(" ")
+		);
+	}
+
+	for y in 0 .. emulator.get_display_height()
+	{
+		result.push(
+			(0 .. emulator.get_display_width())
+				.map(|x| {
+					if emulator.get_display_pixel(x, y)
+					{
+						'#'
+					}
+					else
+					{
+						'.'
+					}
+				})
+				.collect()
+		);
+	}
+
+	return result;
+}
@@ -0,0 +1,14 @@
+pub mod sprites;
+pub use sprites::*;
+
+pub mod disassembler;
+pub use disassembler::*;
+
+pub mod lockstep;
+pub use lockstep::*;
+
+pub mod text_recognition;
+pub use text_recognition::*;
+
+pub mod fuzz;
+pub use fuzz::*;
@@ -0,0 +1,252 @@
+use emulator_common::{color_from_json, Image};
+use serde_json::Value;
+
+use crate::{Components, Configs};
+
+pub struct SpriteSheetConfig
+{
+	pub sprite_width: u8,
+	pub sprite_height: u8,
+	pub columns: usize,
+	pub scale: usize,
+	pub foreground: u32,
+	pub background: u32,
+	pub grid: u32
+}
+
+impl SpriteSheetConfig
+{
+	pub fn default() -> Self
+	{
+		Self {
+			sprite_width: 8,
+			sprite_height: 8,
+			columns: 16,
+			scale: 4,
+			foreground: 0xFFFFFF,
+			background: 0x000000,
+			grid: 0x404040
+		}
+	}
+
+	pub fn from_json(data: &Value) -> Self
+	{
+		let mut result = Self::default();
+
+		// Change the defaults if they are changed in the config.
+		result.sprite_width = match data["sprite_width"].as_u64().unwrap_or(8)
+		{
+			16 => 16,
+			_ => 8
+		};
+		result.sprite_height = clamp_height(
+			data["sprite_height"]
+				.as_u64()
+				.unwrap_or(result.sprite_height as u64)
+		);
+		result.columns = data["columns"]
+			.as_u64()
+			.unwrap_or(result.columns as u64)
+			.max(1) as usize;
+		result.scale = data["scale"]
+			.as_u64()
+			.unwrap_or(result.scale as u64)
+			.max(1) as usize;
+		result.foreground = color_from_json(&data["foreground"], result.foreground);
+		result.background = color_from_json(&data["background"], result.background);
+		result.grid = color_from_json(&data["grid"], result.grid);
+
+		return result;
+	}
+}
+
+#[inline]
+fn clamp_height(height: u64) -> u8 { return height.clamp(1, 32) as u8; }
+
+// Draws the sprite bytes onto a fresh display, one byte per row for 8 pixel wide
+// sprites and two bytes per row for 16 pixel wide ones.
+pub fn render_sprite(data: &[u8], width: u8, height: u8) -> Components::Display
+{
+	let mut display = Components::Display::new(&Configs::DisplayConfig { width, height });
+	let bytes_per_row = width as usize / 8;
+
+	for y in 0 .. height
+	{
+		for column in 0 .. bytes_per_row
+		{
+			let byte = data
+				.get(y as usize * bytes_per_row + column)
+				.copied()
+				.unwrap_or(0);
+
+			for bit in 0 .. 8u8
+			{
+				display.set_pixel(
+					column as u8 * 8 + bit,
+					y,
+					(byte >> (7 - bit)) & 1 == 1
+				);
+			}
+		}
+	}
+
+	return display;
+}
+
+// Reads a sprite straight from memory, anything past the end of RAM reads as zero.
+pub fn render_sprite_from_ram(
+	ram: &Components::RAM,
+	address: usize,
+	width: u8,
+	height: u8
+) -> Components::Display
+{
+	let length = (width as usize / 8) * height as usize;
+	let data: Vec<u8> = (address .. address + length)
+		.map(|index| {
+			if index < ram.get_size()
+			{
+				ram.read_byte(index)
+			}
+			else
+			{
+				0
+			}
+		})
+		.collect();
+
+	return render_sprite(&data, width, height);
+}
+
+// Lays out the tiles left to right, top to bottom, separated by a one pixel grid.
+pub fn build_sprite_sheet(tiles: &[Components::Display], config: &SpriteSheetConfig) -> Image
+{
+	let tile_width = tiles
+		.iter()
+		.map(|tile| tile.get_width() as usize)
+		.max()
+		.unwrap_or(config.sprite_width as usize);
+	let tile_height = tiles
+		.iter()
+		.map(|tile| tile.get_height() as usize)
+		.max()
+		.unwrap_or(config.sprite_height as usize);
+
+	let columns = config.columns.min(tiles.len()).max(1);
+	let rows = tiles.len().div_ceil(columns).max(1);
+
+	let mut result = Image::new(
+		columns * (tile_width + 1) + 1,
+		rows * (tile_height + 1) + 1,
+		config.grid
+	);
+
+	for (index, tile) in tiles.iter().enumerate()
+	{
+		let origin_x = (index % columns) * (tile_width + 1) + 1;
+		let origin_y = (index / columns) * (tile_height + 1) + 1;
+
+		result.fill_rect(
+			origin_x,
+			origin_y,
+			tile_width,
+			tile_height,
+			config.background
+		);
+
+		for y in 0 .. tile.get_height()
+		{
+			for x in 0 .. tile.get_width()
+			{
+				if tile.get_pixel(x, y)
+				{
+					result.set_pixel(
+						origin_x + x as usize,
+						origin_y + y as usize,
+						config.foreground
+					);
+				}
+			}
+		}
+	}
+
+	return result.scaled(config.scale);
+}
+
+// Renders a whole RAM range as consecutive sprites of the configured size.
+pub fn render_memory_range(
+	ram: &Components::RAM,
+	start: usize,
+	length: usize,
+	config: &SpriteSheetConfig
+) -> Image
+{
+	let sprite_size = (config.sprite_width as usize / 8) * config.sprite_height as usize;
+
+	let tiles: Vec<Components::Display> = (start .. start + length)
+		.step_by(sprite_size)
+		.map(|address| {
+			render_sprite_from_ram(
+				ram,
+				address,
+				config.sprite_width,
+				config.sprite_height
+			)
+		})
+		.collect();
+
+	return build_sprite_sheet(&tiles, config);
+}
+
+// Collects every distinct sprite a program draws so it can be exported later.
+pub struct SpriteRipper
+{
+	sprites: Vec<Components::SpriteDraw>,
+	draws: usize
+}
+
+impl SpriteRipper
+{
+	pub fn new() -> Self
+	{
+		Self {
+			sprites: Vec::new(),
+			draws: 0
+		}
+	}
+
+	pub fn record(&mut self, draws: Vec<Components::SpriteDraw>)
+	{
+		for draw in draws
+		{
+			self.draws += 1;
+
+			let known = self
+				.sprites
+				.iter()
+				.any(|sprite| sprite.index == draw.index && sprite.data == draw.data);
+
+			if !known && !draw.data.is_empty()
+			{
+				self.sprites.push(draw);
+			}
+		}
+	}
+
+	#[inline]
+	pub fn get_sprites(&self) -> &[Components::SpriteDraw] { return &self.sprites; }
+
+	#[inline]
+	pub fn get_draw_count(&self) -> usize { return self.draws; }
+
+	pub fn to_image(&self, config: &SpriteSheetConfig) -> Image
+	{
+		let tiles: Vec<Components::Display> = self
+			.sprites
+			.iter()
+			.map(|sprite| render_sprite(&sprite.data, 8, sprite.data.len() as u8))
+			.collect();
+
+		return build_sprite_sheet(&tiles, config);
+	}
+}
@@ -0,0 +1,391 @@
+use serde_json::Value;
+
+use crate::Components;
+use crate::Components::ram::FONTSET;
+
+// Horizontal gap in pixels between two glyphs that is read as a space.
+const SPACE_GAP: usize = 3;
+
+pub struct Glyph
+{
+	pub character: char,
+	pub width: usize,
+	pub height: usize,
+	pixels: Vec<bool>
+}
+
+impl Glyph
+{
+	// Builds a glyph from sprite rows, most significant bit on the left.
+	pub fn from_rows(character: char, rows: &[u8]) -> Self
+	{
+		let used = rows.iter().fold(0u8, |result, row| result | row);
+		let width = (8 - used.trailing_zeros() as usize).max(1);
+
+		let mut pixels = Vec::with_capacity(width * rows.len());
+		for row in rows
+		{
+			for x in 0 .. width
+			{
+				pixels.push((row >> (7 - x)) & 1 == 1);
+			}
+		}
+
+		Self {
+			character,
+			width,
+			height: rows.len(),
+			pixels
+		}
+	}
+
+	// Builds a glyph from lines of text, where '#' is a lit pixel and anything else is blank.
+	pub fn from_pattern(character: char, lines: &[&str]) -> Self
+	{
+		let width = lines
+			.iter()
+			.map(|line| line.chars().count())
+			.max()
+			.unwrap_or(0)
+			.max(1);
+
+		let mut pixels = Vec::with_capacity(width * lines.len());
+		for line in lines
+		{
+			let mut characters = line.chars();
+			for _ in 0 .. width
+			{
+				pixels.push(characters.next() == Some('#'));
+			}
+		}
+
+		Self {
+			character,
+			width,
+			height: lines.len(),
+			pixels
+		}
+	}
+
+	#[inline]
+	pub fn get_pixel(&self, x: usize, y: usize) -> bool { return self.pixels[y * self.width + x]; }
+
+	#[inline]
+	fn lit_pixels(&self) -> usize { return self.pixels.iter().filter(|pixel| **pixel).count(); }
+}
+
+pub struct GlyphTable
+{
+	glyphs: Vec<Glyph>
+}
+
+impl GlyphTable
+{
+	pub fn new() -> Self { Self { glyphs: Vec::new() } }
+
+	// The 0-F hexadecimal font every CHIP8 program has in memory.
+	pub fn builtin() -> Self
+	{
+		let mut result = Self::new();
+
+		for (digit, rows) in FONTSET.chunks(5).enumerate()
+		{
+			let character = std::char::from_digit(digit as u32, 16)
+				.unwrap()
+				.to_ascii_uppercase();
+			result.add(Glyph::from_rows(character, rows));
+		}
+
+		return result;
+	}
+
+	// The built-in font plus any glyphs listed in the config, either as sprite rows
+	// { "char": "K", "rows": [144, 160, 192, 160, 144] } or as a picture
+	// { "char": "K", "pattern": ["#  #", "# # ", "##  ", "# # ", "#  #"] }.
+	pub fn from_json(data: &Value) -> Self
+	{
+		let mut result = Self::builtin();
+
+		for entry in data.as_array().unwrap_or(&Vec::new())
+		{
+			let character = match entry["char"].as_str().and_then(|text| text.chars().next())
+			{
+				Some(character) => character,
+				None =>
+				{
+					warn!("Ignoring glyph without a character: {}", entry);
+					continue;
+				},
+			};
+
+			if let Some(rows) = entry["rows"].as_array()
+			{
+				let rows: Vec<u8> = rows
+					.iter()
+					.map(|row| row.as_u64().unwrap_or(0) as u8)
+					.collect();
+				result.add(Glyph::from_rows(character, &rows));
+			}
+			else if let Some(lines) = entry["pattern"].as_array()
+			{
+				let lines: Vec<&str> = lines.iter().map(|line| line.as_str().unwrap_or("")).collect();
+				result.add(Glyph::from_pattern(character, &lines));
+			}
+			else
+			{
+				warn!("Ignoring glyph '{}' without rows or pattern.", character);
+			}
+		}
+
+		return result;
+	}
+
+	pub fn add(&mut self, glyph: Glyph)
+	{
+		if glyph.height == 0 || glyph.lit_pixels() == 0
+		{
+			warn!("Ignoring empty glyph '{}'.", glyph.character);
+			return;
+		}
+
+		self.glyphs.push(glyph);
+
+		// Try the most detailed glyphs first so they win over ones they contain.
+		self.glyphs
+			.sort_by_key(|glyph| std::cmp::Reverse(glyph.lit_pixels()));
+	}
+
+	#[inline]
+	pub fn get_glyphs(&self) -> &[Glyph] { return &self.glyphs; }
+}
+
+struct Match
+{
+	x: usize,
+	y: usize,
+	width: usize,
+	height: usize,
+	character: char
+}
+
+fn matches_at(display: &Components::Display, glyph: &Glyph, x: usize, y: usize) -> bool
+{
+	if x + glyph.width > display.get_width() as usize || y + glyph.height > display.get_height() as usize
+	{
+		return false;
+	}
+
+	for glyph_y in 0 .. glyph.height
+	{
+		for glyph_x in 0 .. glyph.width
+		{
+			let pixel = display.get_pixel((x + glyph_x) as u8, (y + glyph_y) as u8);
+			if pixel != glyph.get_pixel(glyph_x, glyph_y)
+			{
+				return false;
+			}
+		}
+	}
+
+	return true;
+}
+
+// Reads the screen as text, one line per row of glyphs with spaces for wide gaps.
+pub fn recognize_text(display: &Components::Display, table: &GlyphTable) -> String
+{
+	let width = display.get_width() as usize;
+	let height = display.get_height() as usize;
+	let mut used = vec![false; width * height];
+	let mut matches: Vec<Match> = Vec::new();
+
+	for y in 0 .. height
+	{
+		for x in 0 .. width
+		{
+			// Every glyph has a lit pixel, so only lit unclaimed pixels can start one.
+			if used[y * width + x] || !display.get_pixel(x as u8, y as u8)
+			{
+				continue;
+			}
+
+			for glyph in table.get_glyphs()
+			{
+				// Anchor each glyph on its first lit pixel.
+				let first = glyph.pixels.iter().position(|pixel| *pixel).unwrap();
+				let (offset_x, offset_y) = (first % glyph.width, first / glyph.width);
+				if offset_x > x || offset_y > y
+				{
+					continue;
+				}
+
+				let (origin_x, origin_y) = (x - offset_x, y - offset_y);
+				if !matches_at(display, glyph, origin_x, origin_y)
+				{
+					continue;
+				}
+
+				for glyph_y in 0 .. glyph.height
+				{
+					for glyph_x in 0 .. glyph.width
+					{
+						used[(origin_y + glyph_y) * width + origin_x + glyph_x] = true;
+					}
+				}
+
+				matches.push(Match {
+					x: origin_x,
+					y: origin_y,
+					width: glyph.width,
+					height: glyph.height,
+					character: glyph.character
+				});
+				break;
+			}
+		}
+	}
+
+	// Group the matches into lines, glyphs whose vertical span overlaps share a line.
+	matches.sort_by_key(|found| (found.y, found.x));
+	let mut lines: Vec<Vec<Match>> = Vec::new();
+	for found in matches
+	{
+		let line = lines.iter_mut().find(|line| {
+			line.iter()
+				.any(|other| found.y < other.y + other.height && other.y < found.y + found.height)
+		});
+
+		match line
+		{
+			Some(line) => line.push(found),
+			None => lines.push(vec![found])
+		}
+	}
+
+	let mut result = Vec::new();
+	for mut line in lines
+	{
+		line.sort_by_key(|found| found.x);
+
+		let mut text = String::new();
+		let mut end = None;
+		for found in line
+		{
+			if let Some(end) = end
+			{
+				if found.x >= end + SPACE_GAP
+				{
+					text.push(' ');
+				}
+			}
+
+			text.push(found.character);
+			end = Some(found.x + found.width);
+		}
+
+		result.push(text);
+	}
+
+	return result.join("\n");
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use crate::Configs;
+
+	fn display() -> Components::Display { return Components::Display::new(&Configs::DisplayConfig::default()); }
+
+	// Draws a built-in font digit with its sprite box at x, y.
+	fn draw_digit(display: &mut Components::Display, digit: usize, x: u8, y: u8)
+	{
+		for (row, bits) in FONTSET[digit * 5 .. digit * 5 + 5].iter().enumerate()
+		{
+			for bit in 0 .. 8u8
+			{
+				display.set_pixel(x + bit, y + row as u8, (bits >> (7 - bit)) & 1 == 1);
+			}
+		}
+	}
+
+	#[test]
+	fn reads_built_in_digits()
+	{
+		let mut display = display();
+		draw_digit(&mut display, 0xF, 0, 0);
+		draw_digit(&mut display, 0x0, 5, 0);
+
+		assert_eq!(recognize_text(&display, &GlyphTable::builtin()), "F0");
+	}
+
+	#[test]
+	fn wide_gaps_are_spaces()
+	{
+		// A spans columns 0 to 3, a gap of SPACE_GAP or more after it is a space.
+		let mut display = display();
+		draw_digit(&mut display, 0xA, 0, 0);
+		draw_digit(&mut display, 0x1, 4 + SPACE_GAP as u8, 0);
+
+		assert_eq!(recognize_text(&display, &GlyphTable::builtin()), "A 1");
+
+		let mut display = self::display();
+		draw_digit(&mut display, 0xA, 0, 0);
+		draw_digit(&mut display, 0x1, 4 + SPACE_GAP as u8 - 1, 0);
+
+		assert_eq!(recognize_text(&display, &GlyphTable::builtin()), "A1");
+	}
+
+	#[test]
+	fn narrow_glyphs_are_anchored_at_their_first_lit_pixel()
+	{
+		// The 1 is lit from its third column on, its sprite box starts two pixels further left.
+		let mut display = display();
+		draw_digit(&mut display, 0x1, 0, 0);
+		draw_digit(&mut display, 0x1, 20, 3);
+
+		assert_eq!(recognize_text(&display, &GlyphTable::builtin()), "1 1");
+	}
+
+	#[test]
+	fn rows_of_glyphs_are_lines()
+	{
+		let mut display = display();
+		draw_digit(&mut display, 0x1, 0, 0);
+		// Two rows lower still overlaps the 1, so it is on the same line.
+		draw_digit(&mut display, 0x2, 5, 2);
+		draw_digit(&mut display, 0xC, 0, 10);
+		draw_digit(&mut display, 0xD, 5, 10);
+
+		assert_eq!(recognize_text(&display, &GlyphTable::builtin()), "12\nCD");
+	}
+
+	#[test]
+	fn config_glyphs_read_ok()
+	{
+		let o = [0x60, 0x90, 0x90, 0x90, 0x60];
+		let k = ["#  #", "# # ", "##  ", "# # ", "#  #"];
+		let table = GlyphTable::from_json(&serde_json::json!([
+			{ "char": "O", "rows": o },
+			{ "char": "K", "pattern": k },
+			{ "pattern": ["#"] }
+		]));
+
+		// The entry without a character is left out.
+		assert_eq!(table.get_glyphs().len(), 18);
+
+		let mut display = display();
+		for (y, (bits, line)) in o.iter().zip(k).enumerate()
+		{
+			for x in 0 .. 4u8
+			{
+				display.set_pixel(10 + x, 4 + y as u8, (bits >> (7 - x)) & 1 == 1);
+			}
+			for (x, character) in line.chars().enumerate()
+			{
+				display.set_pixel(15 + x as u8, 4 + y as u8, character == '#');
+			}
+		}
+
+		assert_eq!(recognize_text(&display, &table), "OK");
+	}
+}
@@ -1,5 +0,0 @@
-pub mod raylib_frontend;
-pub use raylib_frontend::*;
-
-pub mod terminal_frontend;
-pub use terminal_frontend::*;
@@ -0,0 +1,435 @@
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+
+// A block of mono samples in -1.0 ..= 1.0, produced or consumed at the given rate.
+#[derive(Clone)]
+pub struct SampleBuffer
+{
+	rate: u32,
+	samples: Vec<f32>
+}
+
+impl SampleBuffer
+{
+	pub fn new(rate: u32) -> Self
+	{
+		Self {
+			rate,
+			samples: Vec::new()
+		}
+	}
+
+	pub fn from_samples(rate: u32, samples: Vec<f32>) -> Self { Self { rate, samples } }
+
+	#[inline]
+	pub fn get_rate(&self) -> u32 { return self.rate; }
+
+	#[inline]
+	pub fn get_samples(&self) -> &[f32] { return &self.samples; }
+
+	#[inline]
+	pub fn len(&self) -> usize { return self.samples.len(); }
+
+	#[inline]
+	pub fn is_empty(&self) -> bool { return self.samples.is_empty(); }
+
+	// Length of the block in seconds.
+	#[inline]
+	pub fn get_duration(&self) -> f64 { return self.samples.len() as f64 / self.rate as f64; }
+
+	#[inline]
+	pub fn push(&mut self, sample: f32) { self.samples.push(sample); }
+
+	#[inline]
+	pub fn extend(&mut self, samples: &[f32]) { self.samples.extend_from_slice(samples); }
+
+	#[inline]
+	pub fn clear(&mut self) { self.samples.clear(); }
+
+	// Drops everything but the newest count samples, for producers nobody is consuming.
+	pub fn keep_newest(&mut self, count: usize)
+	{
+		if self.samples.len() > count
+		{
+			self.samples.drain(0 .. self.samples.len() - count);
+		}
+	}
+}
+
+// Fixed size queue between a core producing samples and whatever plays them.
+// A consumer that falls behind loses the oldest samples, one that runs ahead gets silence.
+pub struct AudioRing
+{
+	data: Vec<f32>,
+	read: usize,
+	length: usize
+}
+
+impl AudioRing
+{
+	pub fn new(capacity: usize) -> Self
+	{
+		Self {
+			data: vec![0.0; capacity.max(1)],
+			read: 0,
+			length: 0
+		}
+	}
+
+	#[inline]
+	pub fn get_capacity(&self) -> usize { return self.data.len(); }
+
+	#[inline]
+	pub fn len(&self) -> usize { return self.length; }
+
+	#[inline]
+	pub fn is_empty(&self) -> bool { return self.length == 0; }
+
+	pub fn push(&mut self, samples: &[f32])
+	{
+		let capacity = self.data.len();
+
+		for sample in samples
+		{
+			if self.length == capacity
+			{
+				// Full, drop the oldest sample.
+				self.read = (self.read + 1) % capacity;
+				self.length -= 1;
+			}
+
+			self.data[(self.read + self.length) % capacity] = *sample;
+			self.length += 1;
+		}
+	}
+
+	// Fills the whole output, padding with silence. Returns how many queued samples were used.
+	pub fn pop(&mut self, output: &mut [f32]) -> usize
+	{
+		let count = output.len().min(self.length);
+		let capacity = self.data.len();
+
+		for (index, sample) in output.iter_mut().enumerate()
+		{
+			*sample = if index < count { self.data[(self.read + index) % capacity] } else { 0.0 };
+		}
+
+		self.read = (self.read + count) % capacity;
+		self.length -= count;
+
+		return count;
+	}
+
+	pub fn clear(&mut self)
+	{
+		self.read = 0;
+		self.length = 0;
+	}
+}
+
+// Linear interpolation between two rates. It keeps its position between blocks,
+// so a stream can be converted a frame at a time without clicks at the seams.
+pub struct Resampler
+{
+	input_rate: u32,
+	output_rate: u32,
+	// Position of the next output sample, in input samples, counted from the last sample of the previous block.
+	position: f64,
+	previous: f32
+}
+
+impl Resampler
+{
+	pub fn new(input_rate: u32, output_rate: u32) -> Self
+	{
+		Self {
+			input_rate: input_rate.max(1),
+			output_rate: output_rate.max(1),
+			position: 0.0,
+			previous: 0.0
+		}
+	}
+
+	#[inline]
+	pub fn get_input_rate(&self) -> u32 { return self.input_rate; }
+
+	#[inline]
+	pub fn get_output_rate(&self) -> u32 { return self.output_rate; }
+
+	pub fn process(&mut self, input: &[f32], output: &mut Vec<f32>)
+	{
+		if self.input_rate == self.output_rate
+		{
+			output.extend_from_slice(input);
+			return;
+		}
+
+		if input.is_empty()
+		{
+			return;
+		}
+
+		let step = self.input_rate as f64 / self.output_rate as f64;
+		let sample_at = |index: usize| -> f32 {
+			return match index
+			{
+				0 => self.previous,
+				index => input[index - 1]
+			};
+		};
+
+		// Index 0 is the last sample of the previous block, the block itself follows.
+		while self.position < input.len() as f64
+		{
+			let index = self.position as usize;
+			let fraction = (self.position - index as f64) as f32;
+
+			output.push(sample_at(index) * (1.0 - fraction) + sample_at(index + 1) * fraction);
+			self.position += step;
+		}
+
+		self.position -= input.len() as f64;
+		self.previous = input[input.len() - 1];
+	}
+
+	pub fn process_buffer(&mut self, input: &SampleBuffer) -> SampleBuffer
+	{
+		let mut result = SampleBuffer::new(self.output_rate);
+		self.process(input.get_samples(), &mut result.samples);
+
+		return result;
+	}
+
+	pub fn reset(&mut self)
+	{
+		self.position = 0.0;
+		self.previous = 0.0;
+	}
+}
+
+// Signed 16 bit PCM, what the WAV sink stores.
+#[inline]
+pub fn sample_to_i16(sample: f32) -> i16 { return (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16; }
+
+// Header of a mono 16 bit PCM WAV file holding the given number of samples.
+pub fn wav_header(rate: u32, sample_count: u32) -> [u8; 44]
+{
+	let data_size = sample_count * 2;
+	let mut result = [0; 44];
+
+	result[0 .. 4].copy_from_slice(b"RIFF");
+	result[4 .. 8].copy_from_slice(&(36 + data_size).to_le_bytes());
+	result[8 .. 12].copy_from_slice(b"WAVE");
+
+	result[12 .. 16].copy_from_slice(b"fmt ");
+	result[16 .. 20].copy_from_slice(&16u32.to_le_bytes());
+	// PCM, one channel.
+	result[20 .. 22].copy_from_slice(&1u16.to_le_bytes());
+	result[22 .. 24].copy_from_slice(&1u16.to_le_bytes());
+	result[24 .. 28].copy_from_slice(&rate.to_le_bytes());
+	// Byte rate, block align and bits per sample.
+	result[28 .. 32].copy_from_slice(&(rate * 2).to_le_bytes());
+	result[32 .. 34].copy_from_slice(&2u16.to_le_bytes());
+	result[34 .. 36].copy_from_slice(&16u16.to_le_bytes());
+
+	result[36 .. 40].copy_from_slice(b"data");
+	result[40 .. 44].copy_from_slice(&data_size.to_le_bytes());
+
+	return result;
+}
+
+// A whole buffer as a WAV file in memory.
+pub fn encode_wav(buffer: &SampleBuffer) -> Vec<u8>
+{
+	let mut result = wav_header(buffer.get_rate(), buffer.len() as u32).to_vec();
+
+	for sample in buffer.get_samples()
+	{
+		result.extend_from_slice(&sample_to_i16(*sample).to_le_bytes());
+	}
+
+	return result;
+}
+
+// Streams samples into a WAV file, no audio hardware needed. The header is
+// fixed up with the final length in finish, a file that was never finished claims to be empty.
+pub struct WavSink
+{
+	writer: BufWriter<File>,
+	rate: u32,
+	sample_count: u32
+}
+
+impl WavSink
+{
+	pub fn create(path: &str, rate: u32) -> std::io::Result<Self>
+	{
+		let mut writer = BufWriter::new(File::create(path)?);
+		writer.write_all(&wav_header(rate, 0))?;
+
+		return Ok(Self {
+			writer,
+			rate,
+			sample_count: 0
+		});
+	}
+
+	#[inline]
+	pub fn get_rate(&self) -> u32 { return self.rate; }
+
+	#[inline]
+	pub fn get_sample_count(&self) -> u32 { return self.sample_count; }
+
+	pub fn write(&mut self, samples: &[f32]) -> std::io::Result<()>
+	{
+		for sample in samples
+		{
+			self.writer.write_all(&sample_to_i16(*sample).to_le_bytes())?;
+		}
+		self.sample_count += samples.len() as u32;
+
+		return Ok(());
+	}
+
+	pub fn finish(mut self) -> std::io::Result<()>
+	{
+		self.writer.seek(SeekFrom::Start(0))?;
+		self.writer.write_all(&wav_header(self.rate, self.sample_count))?;
+		self.writer.flush()?;
+
+		return Ok(());
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn ring_drops_the_oldest_samples_when_full()
+	{
+		let mut ring = AudioRing::new(4);
+		ring.push(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+
+		assert_eq!(ring.len(), 4);
+
+		let mut output = [0.0; 4];
+		assert_eq!(ring.pop(&mut output), 4);
+		assert_eq!(output, [3.0, 4.0, 5.0, 6.0]);
+		assert!(ring.is_empty());
+	}
+
+	#[test]
+	fn ring_pads_with_silence_when_empty()
+	{
+		let mut ring = AudioRing::new(8);
+		ring.push(&[0.5, -0.5]);
+
+		let mut output = [1.0; 5];
+		assert_eq!(ring.pop(&mut output), 2);
+		assert_eq!(output, [0.5, -0.5, 0.0, 0.0, 0.0]);
+
+		assert_eq!(ring.pop(&mut output), 0);
+		assert_eq!(output, [0.0; 5]);
+	}
+
+	#[test]
+	fn ring_wraps_around_its_storage()
+	{
+		let mut ring = AudioRing::new(3);
+		let mut output = [0.0; 2];
+
+		ring.push(&[1.0, 2.0]);
+		ring.pop(&mut output);
+		ring.push(&[3.0, 4.0, 5.0]);
+
+		let mut output = [0.0; 3];
+		assert_eq!(ring.pop(&mut output), 3);
+		assert_eq!(output, [3.0, 4.0, 5.0]);
+	}
+
+	#[test]
+	fn resampler_output_length_follows_the_rates()
+	{
+		// One second of BytePusher audio, 60 frames of 256 samples.
+		let mut resampler = Resampler::new(15360, 48000);
+		let mut output = Vec::new();
+
+		for _ in 0 .. 60
+		{
+			let before = output.len();
+			resampler.process(&[0.25; 256], &mut output);
+
+			let produced = output.len() - before;
+			assert!((799 ..= 801).contains(&produced), "{} samples from one frame", produced);
+		}
+
+		assert!((47999 ..= 48001).contains(&output.len()), "{} samples from one second", output.len());
+	}
+
+	#[test]
+	fn resampler_is_continuous_across_blocks()
+	{
+		let input: Vec<f32> = (0 .. 1000).map(|index| (index as f32 * 0.05).sin()).collect();
+
+		let mut whole = Vec::new();
+		Resampler::new(15360, 48000).process(&input, &mut whole);
+
+		let mut pieces = Vec::new();
+		let mut resampler = Resampler::new(15360, 48000);
+		for block in input.chunks(77)
+		{
+			resampler.process(block, &mut pieces);
+		}
+
+		// Rounding in the carried position may move the very last sample over the end of the input.
+		assert!(whole.len().abs_diff(pieces.len()) <= 1);
+		for (index, (a, b)) in whole.iter().zip(&pieces).enumerate()
+		{
+			assert!((a - b).abs() < 1e-4, "sample {} is {} in one block and {} in pieces", index, a, b);
+		}
+
+		// No jumps bigger than the input itself makes.
+		for pair in pieces.windows(2)
+		{
+			assert!((pair[1] - pair[0]).abs() <= 0.05);
+		}
+	}
+
+	#[test]
+	fn wav_header_describes_mono_16_bit_pcm()
+	{
+		let header = wav_header(15360, 1000);
+		let u32_at = |offset: usize| -> u32 {
+			return u32::from_le_bytes(header[offset .. offset + 4].try_into().unwrap());
+		};
+		let u16_at = |offset: usize| -> u16 {
+			return u16::from_le_bytes(header[offset .. offset + 2].try_into().unwrap());
+		};
+
+		assert_eq!(&header[0 .. 4], b"RIFF");
+		assert_eq!(u32_at(4), 36 + 2000);
+		assert_eq!(&header[8 .. 16], b"WAVEfmt ");
+
+		assert_eq!(u32_at(16), 16);
+		assert_eq!(u16_at(20), 1);
+		assert_eq!(u16_at(22), 1);
+		assert_eq!(u32_at(24), 15360);
+		assert_eq!(u32_at(28), 30720);
+		assert_eq!(u16_at(32), 2);
+		assert_eq!(u16_at(34), 16);
+
+		assert_eq!(&header[36 .. 40], b"data");
+		assert_eq!(u32_at(40), 2000);
+	}
+
+	#[test]
+	fn encoded_wav_is_header_and_samples()
+	{
+		let wav = encode_wav(&SampleBuffer::from_samples(8000, vec![0.0, 1.0, -1.0]));
+
+		assert_eq!(wav.len(), 44 + 6);
+		assert_eq!(&wav[44 ..], &[0x00, 0x00, 0xFF, 0x7F, 0x01, 0x80]);
+	}
+}
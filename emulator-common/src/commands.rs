@@ -0,0 +1,7 @@
+// Requests a frontend can raise on top of the emulated keypad, handled by the application.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FrontendCommand
+{
+	DumpMemory,
+	InjectMemory
+}
@@ -0,0 +1,152 @@
+use crate::Image;
+
+// Layout of a single pixel in a framebuffer.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PixelFormat
+{
+	// Red, green, blue and alpha, one byte each, the layout textures are usually uploaded in.
+	Rgba8888
+}
+
+impl PixelFormat
+{
+	#[inline]
+	pub fn bytes_per_pixel(&self) -> usize
+	{
+		return match self
+		{
+			Self::Rgba8888 => 4
+		};
+	}
+}
+
+// A whole screen in one contiguous buffer, rows top to bottom without padding.
+pub struct Framebuffer
+{
+	width: usize,
+	height: usize,
+	format: PixelFormat,
+	data: Vec<u8>
+}
+
+impl Framebuffer
+{
+	// Opaque black RGBA framebuffer.
+	pub fn new(width: usize, height: usize) -> Self
+	{
+		let mut result = Self {
+			width,
+			height,
+			format: PixelFormat::Rgba8888,
+			data: vec![0; width * height * PixelFormat::Rgba8888.bytes_per_pixel()]
+		};
+		result.fill(0x000000);
+
+		return result;
+	}
+
+	#[inline]
+	pub fn get_width(&self) -> usize { return self.width; }
+
+	#[inline]
+	pub fn get_height(&self) -> usize { return self.height; }
+
+	#[inline]
+	pub fn get_format(&self) -> PixelFormat { return self.format; }
+
+	// Bytes between the start of two rows.
+	#[inline]
+	pub fn get_stride(&self) -> usize { return self.width * self.format.bytes_per_pixel(); }
+
+	#[inline]
+	pub fn get_data(&self) -> &[u8] { return &self.data; }
+
+	#[inline]
+	pub fn get_data_mut(&mut self) -> &mut [u8] { return &mut self.data; }
+
+	#[inline]
+	pub fn get_row(&self, y: usize) -> &[u8]
+	{
+		let stride = self.get_stride();
+		return &self.data[y * stride .. (y + 1) * stride];
+	}
+
+	// Reads a pixel as 0xRRGGBB.
+	#[inline]
+	pub fn get_pixel(&self, x: usize, y: usize) -> u32
+	{
+		let index = (y * self.width + x) * self.format.bytes_per_pixel();
+		let pixel = &self.data[index .. index + 3];
+
+		return (pixel[0] as u32) << 16 | (pixel[1] as u32) << 8 | pixel[2] as u32;
+	}
+
+	// Writes an opaque 0xRRGGBB pixel.
+	#[inline]
+	pub fn set_pixel(&mut self, x: usize, y: usize, color: u32)
+	{
+		let index = (y * self.width + x) * self.format.bytes_per_pixel();
+		self.data[index .. index + 4].copy_from_slice(&rgba_bytes(color));
+	}
+
+	pub fn fill(&mut self, color: u32)
+	{
+		let bytes = rgba_bytes(color);
+		for pixel in self.data.chunks_exact_mut(4)
+		{
+			pixel.copy_from_slice(&bytes);
+		}
+	}
+
+	// Returns a copy shrunk by a factor, every factor x factor block averaged into one pixel.
+	// Blocks at the right and bottom edges may be partial.
+	pub fn downscaled(&self, factor: usize) -> Self
+	{
+		let factor = factor.max(1);
+		let mut result = Self::new(self.width.div_ceil(factor), self.height.div_ceil(factor));
+
+		for y in 0 .. result.height
+		{
+			for x in 0 .. result.width
+			{
+				let mut sums = [0; 3];
+				let mut count = 0;
+
+				for source_y in y * factor .. ((y + 1) * factor).min(self.height)
+				{
+					for source_x in x * factor .. ((x + 1) * factor).min(self.width)
+					{
+						let color = self.get_pixel(source_x, source_y);
+						sums[0] += (color >> 16) & 0xFF;
+						sums[1] += (color >> 8) & 0xFF;
+						sums[2] += color & 0xFF;
+						count += 1;
+					}
+				}
+
+				result.set_pixel(x, y, (sums[0] / count) << 16 | (sums[1] / count) << 8 | (sums[2] / count));
+			}
+		}
+
+		return result;
+	}
+
+	pub fn to_image(&self) -> Image
+	{
+		let mut result = Image::new(self.width, self.height, 0);
+
+		for y in 0 .. self.height
+		{
+			for x in 0 .. self.width
+			{
+				result.set_pixel(x, y, self.get_pixel(x, y));
+			}
+		}
+
+		return result;
+	}
+}
+
+// Opaque RGBA bytes of a 0xRRGGBB colour, handy for filling framebuffers a pixel at a time.
+#[inline]
+pub fn rgba_bytes(color: u32) -> [u8; 4] { return [(color >> 16) as u8, (color >> 8) as u8, color as u8, 0xFF]; }
@@ -0,0 +1,62 @@
+use serde_json::Value;
+
+use crate::{FrontendCommand, SampleBuffer, System};
+
+// A window or terminal any platform can run in. Frontends only see the System trait:
+// the screen as 0xRRGGBB pixels and a keypad of get_key_count() keys.
+pub trait Frontend
+{
+	// Reads the input, forwarding keypad state to the emulator.
+	fn update(&mut self, system: &mut dyn System, delta: f64);
+
+	fn draw(&mut self, system: &mut dyn System);
+
+	// Queues samples taken from the emulator for playback, frontends without sound ignore them.
+	fn play_audio(&mut self, _samples: &SampleBuffer) {}
+
+	fn has_quit(&self) -> bool;
+
+	// Application hotkeys (memory dump, injection, ...) pressed since the last call.
+	fn take_commands(&mut self) -> Vec<FrontendCommand>;
+}
+
+// Maps keypad keys to host keys, read from the "keys" array of a frontend config where
+// entry N is the host key for keypad key N. Missing entries leave the key unbound.
+pub fn key_names_from_json(data: &Value, key_count: usize) -> Vec<String>
+{
+	let mut result = vec![String::new(); key_count];
+
+	if let Some(keys) = data["keys"].as_array()
+	{
+		for (index, key) in keys.iter().take(key_count).enumerate()
+		{
+			result[index] = key.as_str().unwrap_or("").to_string();
+		}
+
+		if keys.len() > key_count
+		{
+			warn!(
+				"Ignoring {} key bindings past the {} keys of the keypad.",
+				keys.len() - key_count,
+				key_count
+			);
+		}
+	}
+
+	return result;
+}
+
+// Reads a { "r": 0, "g": 0, "b": 0 } colour as 0xRRGGBB, missing channels keep the default.
+pub fn color_from_json(data: &Value, default: u32) -> u32
+{
+	if !data.is_object()
+	{
+		return default;
+	}
+
+	let r = data["r"].as_u64().unwrap_or((default >> 16) as u64 & 0xFF) as u32;
+	let g = data["g"].as_u64().unwrap_or((default >> 8) as u64 & 0xFF) as u32;
+	let b = data["b"].as_u64().unwrap_or(default as u64 & 0xFF) as u32;
+
+	return (r & 0xFF) << 16 | (g & 0xFF) << 8 | (b & 0xFF);
+}
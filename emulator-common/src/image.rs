@@ -0,0 +1,323 @@
+use std::fs::File;
+use std::io::{BufWriter, Error, ErrorKind, Write};
+
+use crate::fnv1a_64;
+
+// Simple RGB image, pixels are stored as 0xRRGGBB.
+pub struct Image
+{
+	width: usize,
+	height: usize,
+	pixels: Vec<u32>
+}
+
+impl Image
+{
+	pub fn new(width: usize, height: usize, color: u32) -> Self
+	{
+		Self {
+			width,
+			height,
+			pixels: vec![color; width * height]
+		}
+	}
+
+	#[inline]
+	pub fn get_width(&self) -> usize { return self.width; }
+
+	#[inline]
+	pub fn get_height(&self) -> usize { return self.height; }
+
+	#[inline]
+	pub fn get_pixel(&self, x: usize, y: usize) -> u32
+	{
+		if x >= self.width || y >= self.height
+		{
+			return 0;
+		}
+
+		return self.pixels[y * self.width + x];
+	}
+
+	#[inline]
+	pub fn set_pixel(&mut self, x: usize, y: usize, color: u32)
+	{
+		if x >= self.width || y >= self.height
+		{
+			return;
+		}
+
+		self.pixels[y * self.width + x] = color;
+	}
+
+	pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: u32)
+	{
+		for py in y .. y + height
+		{
+			for px in x .. x + width
+			{
+				self.set_pixel(px, py, color);
+			}
+		}
+	}
+
+	// Returns a copy of the image with every pixel blown up to a factor x factor block.
+	pub fn scaled(&self, factor: usize) -> Self
+	{
+		let factor = factor.max(1);
+		let mut result = Self::new(self.width * factor, self.height * factor, 0);
+
+		for y in 0 .. result.height
+		{
+			for x in 0 .. result.width
+			{
+				result.pixels[y * result.width + x] = self.pixels[(y / factor) * self.width + x / factor];
+			}
+		}
+
+		return result;
+	}
+
+	// Hash of the dimensions and pixels, used to compare frames without storing them.
+	pub fn hash(&self) -> u64
+	{
+		let mut data = Vec::with_capacity(16 + self.pixels.len() * 3);
+		data.extend_from_slice(&(self.width as u64).to_le_bytes());
+		data.extend_from_slice(&(self.height as u64).to_le_bytes());
+		for pixel in &self.pixels
+		{
+			data.extend_from_slice(&pixel.to_be_bytes()[1 ..]);
+		}
+
+		return fnv1a_64(&data);
+	}
+
+	// Binary PPM, a format simple enough to also be read back.
+	pub fn to_ppm(&self) -> Vec<u8>
+	{
+		let mut result = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+		for pixel in &self.pixels
+		{
+			result.extend_from_slice(&pixel.to_be_bytes()[1 ..]);
+		}
+
+		return result;
+	}
+
+	pub fn from_ppm(data: &[u8]) -> Result<Self, String>
+	{
+		// Header fields are separated by whitespace, comments run until the end of the line.
+		let mut fields = Vec::new();
+		let mut position = 0;
+		while fields.len() < 4
+		{
+			while position < data.len()
+			{
+				if data[position] == b'#'
+				{
+					while position < data.len() && data[position] != b'\n'
+					{
+						position += 1;
+					}
+				}
+				else if data[position].is_ascii_whitespace()
+				{
+					position += 1;
+				}
+				else
+				{
+					break;
+				}
+			}
+
+			let start = position;
+			while position < data.len() && !data[position].is_ascii_whitespace()
+			{
+				position += 1;
+			}
+
+			if start == position
+			{
+				return Err("truncated PPM header".to_string());
+			}
+
+			fields.push(String::from_utf8_lossy(&data[start .. position]).to_string());
+		}
+
+		// Exactly one whitespace character separates the header from the pixels.
+		position += 1;
+
+		if fields[0] != "P6"
+		{
+			return Err(format!("unsupported PPM type {}", fields[0]));
+		}
+
+		let parse = |field: &String| field.parse::<usize>().map_err(|_| format!("invalid PPM field {}", field));
+		let width = parse(&fields[1])?;
+		let height = parse(&fields[2])?;
+		if parse(&fields[3])? != 255
+		{
+			return Err("only 8-bit PPM images are supported".to_string());
+		}
+
+		let pixels = data.get(position ..).unwrap_or(&[]);
+		if pixels.len() < width * height * 3
+		{
+			return Err("truncated PPM pixel data".to_string());
+		}
+
+		let mut result = Self::new(width, height, 0);
+		for (index, rgb) in pixels.chunks(3).take(width * height).enumerate()
+		{
+			result.pixels[index] = (rgb[0] as u32) << 16 | (rgb[1] as u32) << 8 | rgb[2] as u32;
+		}
+
+		return Ok(result);
+	}
+
+	pub fn save_ppm(&self, path: &str) -> std::io::Result<()> { return std::fs::write(path, self.to_ppm()); }
+
+	pub fn load_ppm(path: &str) -> std::io::Result<Self>
+	{
+		return Self::from_ppm(&std::fs::read(path)?).map_err(|error| Error::new(ErrorKind::InvalidData, error));
+	}
+
+	// Encodes the image as an 8-bit RGB PNG. The zlib stream uses stored blocks only,
+	// which keeps the encoder tiny at the cost of file size.
+	pub fn to_png(&self) -> Vec<u8>
+	{
+		let mut raw = Vec::with_capacity((self.width * 3 + 1) * self.height);
+		for y in 0 .. self.height
+		{
+			// Filter type: none.
+			raw.push(0);
+			for x in 0 .. self.width
+			{
+				let pixel = self.pixels[y * self.width + x];
+				raw.push((pixel >> 16) as u8);
+				raw.push((pixel >> 8) as u8);
+				raw.push(pixel as u8);
+			}
+		}
+
+		let mut zlib = vec![0x78, 0x01];
+		let mut blocks = raw.chunks(0xFFFF).peekable();
+		if blocks.peek().is_none()
+		{
+			zlib.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+		}
+		while let Some(block) = blocks.next()
+		{
+			let length = block.len() as u16;
+			zlib.push(blocks.peek().is_none() as u8);
+			zlib.extend_from_slice(&length.to_le_bytes());
+			zlib.extend_from_slice(&(!length).to_le_bytes());
+			zlib.extend_from_slice(block);
+		}
+		zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+		let mut header = Vec::with_capacity(13);
+		header.extend_from_slice(&(self.width as u32).to_be_bytes());
+		header.extend_from_slice(&(self.height as u32).to_be_bytes());
+		// Bit depth 8, colour type RGB, default compression, filtering and no interlace.
+		header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+		let mut result = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+		write_png_chunk(&mut result, b"IHDR", &header);
+		write_png_chunk(&mut result, b"IDAT", &zlib);
+		write_png_chunk(&mut result, b"IEND", &[]);
+
+		return result;
+	}
+
+	// PNG wrapped in a data URI, handy for embedding into HTML or JSON reports.
+	pub fn to_png_data_uri(&self) -> String
+	{
+		return format!("data:image/png;base64,{}", encode_base64(&self.to_png()));
+	}
+
+	pub fn save_png(&self, path: &str) -> std::io::Result<()>
+	{
+		let mut file = BufWriter::new(File::create(path)?);
+		file.write_all(&self.to_png())?;
+		file.flush()?;
+
+		info!("Saved {}x{} PNG image to {}.", self.width, self.height, path);
+
+		return Ok(());
+	}
+}
+
+fn write_png_chunk(output: &mut Vec<u8>, kind: &[u8; 4], data: &[u8])
+{
+	output.extend_from_slice(&(data.len() as u32).to_be_bytes());
+
+	let start = output.len();
+	output.extend_from_slice(kind);
+	output.extend_from_slice(data);
+
+	let crc = crc32(&output[start ..]);
+	output.extend_from_slice(&crc.to_be_bytes());
+}
+
+pub fn encode_base64(data: &[u8]) -> String
+{
+	const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+	let mut result = String::with_capacity(data.len().div_ceil(3) * 4);
+
+	for chunk in data.chunks(3)
+	{
+		let bytes = [
+			chunk[0],
+			chunk.get(1).copied().unwrap_or(0),
+			chunk.get(2).copied().unwrap_or(0)
+		];
+		let triple = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+
+		for index in 0 .. 4
+		{
+			if index <= chunk.len()
+			{
+				result.push(ALPHABET[(triple >> (18 - index * 6)) as usize & 0x3F] as char);
+			}
+			else
+			{
+				result.push('=');
+			}
+		}
+	}
+
+	return result;
+}
+
+pub fn crc32(data: &[u8]) -> u32
+{
+	let mut crc = 0xFFFFFFFFu32;
+
+	for byte in data
+	{
+		crc ^= *byte as u32;
+		for _ in 0 .. 8
+		{
+			let mask = (!(crc & 1)).wrapping_add(1);
+			crc = (crc >> 1) ^ (0xEDB88320 & mask);
+		}
+	}
+
+	return !crc;
+}
+
+pub fn adler32(data: &[u8]) -> u32
+{
+	let mut a = 1u32;
+	let mut b = 0u32;
+
+	for byte in data
+	{
+		a = (a + *byte as u32) % 65521;
+		b = (b + a) % 65521;
+	}
+
+	return b << 16 | a;
+}
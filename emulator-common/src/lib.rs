@@ -1,9 +1,33 @@
+#[macro_use]
+extern crate log;
+
 mod timers;
 pub use timers::*;
 
 mod maths;
 pub use maths::*;
 
+mod image;
+pub use image::*;
+
+mod framebuffer;
+pub use framebuffer::*;
+
+mod audio;
+pub use audio::*;
+
+mod memory;
+pub use memory::*;
+
+mod commands;
+pub use commands::*;
+
+mod system;
+pub use system::*;
+
+mod frontend;
+pub use frontend::*;
+
 #[inline]
 pub fn sleep_seconds_f64(seconds: f64)
 {
@@ -14,3 +14,17 @@ where
 
 	return input;
 }
+
+// 64-bit FNV-1a, stable across runs and platforms unlike the std hasher.
+pub fn fnv1a_64(data: &[u8]) -> u64
+{
+	let mut hash = 0xCBF29CE484222325u64;
+
+	for byte in data
+	{
+		hash ^= *byte as u64;
+		hash = hash.wrapping_mul(0x100000001B3);
+	}
+
+	return hash;
+}
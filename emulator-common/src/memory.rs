@@ -0,0 +1,205 @@
+use std::fs::{read, write};
+use std::io::{Error, ErrorKind};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MemoryFileFormat
+{
+	Binary,
+	IntelHex
+}
+
+impl MemoryFileFormat
+{
+	// Intel HEX is picked for .hex / .ihx files, everything else is raw binary.
+	pub fn from_path(path: &str) -> Self
+	{
+		let extension = std::path::Path::new(path)
+			.extension()
+			.and_then(|extension| extension.to_str())
+			.unwrap_or("")
+			.to_lowercase();
+
+		return match extension.as_str()
+		{
+			"hex" | "ihx" => Self::IntelHex,
+			_ => Self::Binary
+		};
+	}
+}
+
+// A run of bytes read from a memory file, placed at an offset relative to the load address.
+pub struct MemorySegment
+{
+	pub offset: usize,
+	pub data: Vec<u8>
+}
+
+pub fn to_intel_hex(data: &[u8]) -> String
+{
+	let mut result = String::new();
+	let mut upper_address = 0;
+
+	for (index, chunk) in data.chunks(16).enumerate()
+	{
+		let address = index * 16;
+
+		// Extended linear address record whenever we cross into a new 64 KiB page.
+		if address >> 16 != upper_address
+		{
+			upper_address = address >> 16;
+			push_hex_record(
+				&mut result,
+				0,
+				0x04,
+				&(upper_address as u16).to_be_bytes()
+			);
+		}
+
+		push_hex_record(&mut result, address as u16, 0x00, chunk);
+	}
+
+	push_hex_record(&mut result, 0, 0x01, &[]);
+
+	return result;
+}
+
+fn push_hex_record(output: &mut String, address: u16, kind: u8, data: &[u8])
+{
+	let mut checksum = data.len() as u8;
+	checksum = checksum.wrapping_add((address >> 8) as u8);
+	checksum = checksum.wrapping_add(address as u8);
+	checksum = checksum.wrapping_add(kind);
+
+	output.push_str(&format!(":{:02X}{:04X}{:02X}", data.len(), address, kind));
+	for byte in data
+	{
+		checksum = checksum.wrapping_add(*byte);
+		output.push_str(&format!("{:02X}", byte));
+	}
+	output.push_str(&format!("{:02X}\n", checksum.wrapping_neg()));
+}
+
+pub fn from_intel_hex(text: &str) -> Result<Vec<MemorySegment>, String>
+{
+	let mut result: Vec<MemorySegment> = Vec::new();
+	let mut base = 0;
+
+	for (line_number, line) in text.lines().enumerate()
+	{
+		let line = line.trim();
+		if line.is_empty()
+		{
+			continue;
+		}
+
+		let invalid = |reason: &str| format!("line {}: {}", line_number + 1, reason);
+
+		// Everything below slices the line two bytes at a time, which only lines up with characters in ASCII.
+		if !line.is_ascii() || !line.starts_with(':') || line.len() < 11 || line.len() % 2 == 0
+		{
+			return Err(invalid("malformed record"));
+		}
+
+		let bytes = (1 .. line.len())
+			.step_by(2)
+			.map(|index| u8::from_str_radix(&line[index .. index + 2], 16))
+			.collect::<Result<Vec<u8>, _>>()
+			.map_err(|_| invalid("invalid hex digits"))?;
+
+		let length = bytes[0] as usize;
+		if bytes.len() != length + 5
+		{
+			return Err(invalid("record length mismatch"));
+		}
+
+		if bytes.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte)) != 0
+		{
+			return Err(invalid("checksum mismatch"));
+		}
+
+		let address = (bytes[1] as usize) << 8 | bytes[2] as usize;
+		let data = &bytes[4 .. 4 + length];
+
+		match bytes[3]
+		{
+			// Data.
+			0x00 =>
+			{
+				let offset = base + address;
+
+				// Merge contiguous records into a single segment.
+				match result.last_mut()
+				{
+					Some(last) if last.offset + last.data.len() == offset =>
+					{
+						last.data.extend_from_slice(data);
+					},
+					_ =>
+					{
+						result.push(MemorySegment {
+							offset,
+							data: data.to_vec()
+						});
+					},
+				}
+			},
+
+			// End of file.
+			0x01 => break,
+
+			// Extended segment address.
+			0x02 if length == 2 =>
+			{
+				base = ((data[0] as usize) << 8 | data[1] as usize) << 4;
+			},
+
+			// Extended linear address.
+			0x04 if length == 2 =>
+			{
+				base = ((data[0] as usize) << 8 | data[1] as usize) << 16;
+			},
+
+			// Start addresses have no meaning for a memory image.
+			0x03 | 0x05 =>
+			{},
+
+			kind =>
+			{
+				return Err(invalid(&format!("unsupported record type {:02X}", kind)));
+			},
+		}
+	}
+
+	return Ok(result);
+}
+
+pub fn save_memory_file(path: &str, data: &[u8]) -> std::io::Result<()>
+{
+	match MemoryFileFormat::from_path(path)
+	{
+		MemoryFileFormat::Binary => write(path, data)?,
+		MemoryFileFormat::IntelHex => write(path, to_intel_hex(data))?
+	}
+
+	info!("Dumped {} bytes of memory to {}.", data.len(), path);
+
+	return Ok(());
+}
+
+pub fn load_memory_file(path: &str) -> std::io::Result<Vec<MemorySegment>>
+{
+	let contents = read(path)?;
+
+	return match MemoryFileFormat::from_path(path)
+	{
+		MemoryFileFormat::Binary => Ok(vec![MemorySegment {
+			offset: 0,
+			data: contents
+		}]),
+		MemoryFileFormat::IntelHex =>
+		{
+			from_intel_hex(&String::from_utf8_lossy(&contents))
+				.map_err(|error| Error::new(ErrorKind::InvalidData, error))
+		},
+	};
+}
@@ -0,0 +1,68 @@
+use crate::{Framebuffer, Image, SampleBuffer};
+
+// Everything a platform offers to the outside, so the app and the tools can drive any
+// emulator through one code path. Screen pixels are 0xRRGGBB.
+pub trait System
+{
+	// Loads a ROM from disk, reporting unreadable or oversized files instead of panicking.
+	fn try_load(&mut self, path: &str) -> Result<(), String>;
+
+	fn load_data(&mut self, data: &[u8]) -> Result<(), String>;
+
+	// Puts the machine back into its power-on state with the last loaded ROM in memory.
+	fn reset(&mut self);
+
+	// Runs the emulator for the given ammount of seconds, pacing itself like the real hardware.
+	fn update(&mut self, delta: f64);
+
+	// Runs a single frame as fast as possible, for use without a frontend.
+	fn run_frame(&mut self);
+
+	fn get_key_count(&self) -> usize;
+
+	fn press_key(&mut self, key: u8);
+
+	fn release_key(&mut self, key: u8);
+
+	fn get_screen_width(&self) -> usize;
+
+	fn get_screen_height(&self) -> usize;
+
+	fn get_screen_pixel(&self, x: usize, y: usize) -> u32;
+
+	// The whole screen, refreshed in one pass. Prefer this over get_screen_pixel when drawing every frame.
+	fn frame(&mut self) -> &Framebuffer;
+
+	fn get_screen_image(&self) -> Image
+	{
+		let mut result = Image::new(self.get_screen_width(), self.get_screen_height(), 0);
+
+		for y in 0 .. self.get_screen_height()
+		{
+			for x in 0 .. self.get_screen_width()
+			{
+				result.set_pixel(x, y, self.get_screen_pixel(x, y));
+			}
+		}
+
+		return result;
+	}
+
+	// Whether the screen changed since the last reset_draw_flag, frontends may skip redrawing otherwise.
+	fn get_draw_flag(&self) -> bool { return true; }
+
+	fn reset_draw_flag(&mut self) {}
+
+	fn is_sound_playing(&self) -> bool;
+
+	// Samples produced since the last call, at the platform's own rate. None for platforms without sound.
+	fn take_audio(&mut self) -> Option<SampleBuffer> { return None; }
+
+	fn is_running(&self) -> bool;
+
+	// Why the emulator stopped, if a broken program stopped it.
+	fn get_fault(&self) -> Option<String> { return None; }
+
+	// Makes any randomness reproducible, for platforms that have some.
+	fn seed_random(&mut self, _seed: u64) {}
+}
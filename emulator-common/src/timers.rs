@@ -29,6 +29,7 @@ impl DeltaTimer
 	pub fn get(&mut self) -> f64 { return self.delta; }
 }
 
+#[derive(Clone)]
 pub struct GenericTimerConfig
 {
 	pub rate: f64
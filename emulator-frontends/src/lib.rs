@@ -0,0 +1,19 @@
+// The windows and terminals an emulator can run in. They live apart from emulator-common so the
+// cores and the headless tools build without raylib's native library.
+#[macro_use]
+extern crate log;
+
+mod raylib_frontend;
+pub use raylib_frontend::*;
+
+mod terminal_colors;
+pub use terminal_colors::*;
+
+mod terminal_renderer;
+pub use terminal_renderer::*;
+
+mod terminal_graphics;
+pub use terminal_graphics::*;
+
+mod terminal_frontend;
+pub use terminal_frontend::*;
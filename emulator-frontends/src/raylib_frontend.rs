@@ -0,0 +1,270 @@
+use raylib::{consts::KeyboardKey, core::input::key_from_i32, prelude::*};
+use serde_json::Value;
+
+use emulator_common::{key_names_from_json, AudioRing, Frontend, FrontendCommand, Resampler, SampleBuffer, System};
+
+// Rate the audio device is fed at, whatever the platform produces gets resampled to it.
+const AUDIO_RATE: u32 = 44100;
+// Samples handed to raylib per stream update.
+const AUDIO_CHUNK: usize = 1024;
+
+pub struct RaylibFrontendConfig
+{
+	// Window pixels per emulated pixel, 0 makes the longer side of the window roughly 768 pixels.
+	pub scale: usize,
+	pub show_fps: bool,
+	pub audio: bool,
+	pub keys: Vec<String>
+}
+
+impl RaylibFrontendConfig
+{
+	pub fn default() -> Self
+	{
+		Self {
+			scale: 0,
+			show_fps: false,
+			audio: true,
+			keys: Vec::new()
+		}
+	}
+
+	pub fn from_json(data: &Value, key_count: usize) -> Self
+	{
+		let mut result = Self::default();
+
+		// Change the defaults if they are changed in the config.
+		result.scale = data["scale"].as_u64().unwrap_or(result.scale as u64) as usize;
+		result.show_fps = data["show_fps"].as_bool().unwrap_or(result.show_fps);
+		result.audio = data["audio"].as_bool().unwrap_or(result.audio);
+		result.keys = key_names_from_json(data, key_count);
+
+		info!("Raylib frontend config loaded successfully from JSON data.");
+
+		return result;
+	}
+}
+
+// Only printable keys have a raylib key code equal to their upper case ASCII value.
+fn key_from_name(name: &str) -> KeyboardKey
+{
+	let key = name.chars().next().unwrap_or('\0').to_ascii_uppercase();
+
+	if key.is_ascii_uppercase() || key.is_ascii_digit() || " ',-./;=[\\]`".contains(key)
+	{
+		if let Some(key) = key_from_i32(key as i32)
+		{
+			return key;
+		}
+	}
+
+	if !name.is_empty()
+	{
+		warn!("Unsupported raylib key binding: {}", name);
+	}
+
+	return KeyboardKey::KEY_NULL;
+}
+
+pub struct RaylibFrontend
+{
+	width: usize,
+	height: usize,
+	show_fps: bool,
+	output: Texture2D,
+	// The stream has to close before the device, so it comes first.
+	audio_stream: Option<AudioStream>,
+	audio_device: Option<RaylibAudio>,
+	audio_enabled: bool,
+	audio_ring: AudioRing,
+	resampler: Resampler,
+	internals: (raylib::RaylibHandle, raylib::RaylibThread),
+	bindings: Vec<KeyboardKey>,
+	commands: Vec<FrontendCommand>
+}
+
+impl RaylibFrontend
+{
+	pub fn new(config: &RaylibFrontendConfig, system: &dyn System) -> Self
+	{
+		let width = system.get_screen_width();
+		let height = system.get_screen_height();
+		let scale = match config.scale
+		{
+			0 => (768 / width.max(height)).max(1),
+			scale => scale
+		};
+
+		let mut result_internals = raylib::init()
+			.size((width * scale) as i32, (height * scale) as i32)
+			.title("remu")
+			.vsync()
+			.build();
+
+		// An RGBA texture the framebuffer is uploaded into as is.
+		let output = result_internals
+			.0
+			.load_texture_from_image(
+				&result_internals.1,
+				&Image::gen_image_color(width as i32, height as i32, Color::BLACK)
+			)
+			.unwrap();
+
+		let mut result = Self {
+			width,
+			height,
+			show_fps: config.show_fps,
+			output,
+			audio_stream: None,
+			audio_device: None,
+			audio_enabled: config.audio,
+			// A quarter of a second of latency at most.
+			audio_ring: AudioRing::new(AUDIO_RATE as usize / 4),
+			resampler: Resampler::new(AUDIO_RATE, AUDIO_RATE),
+			internals: result_internals,
+			bindings: config.keys.iter().map(|name| key_from_name(name)).collect(),
+			commands: Vec::new()
+		};
+
+		info!("Raylib frontend initialized successfully.");
+
+		result
+			.internals
+			.0
+			.set_exit_key(Some(KeyboardKey::KEY_ESCAPE));
+
+		return result;
+	}
+
+	// Opens the audio device the first time the emulator has something to play.
+	fn start_audio(&mut self)
+	{
+		let mut device = RaylibAudio::init_audio_device();
+		// 8 bit samples, the binding passes a byte count where raylib expects a sample count.
+		let mut stream = AudioStream::init_audio_stream(&self.internals.1, AUDIO_RATE, 8, 1);
+		device.play_audio_stream(&mut stream);
+
+		self.audio_stream = Some(stream);
+		self.audio_device = Some(device);
+
+		info!("Raylib audio initialized successfully.");
+	}
+
+	// Hands queued samples to raylib whenever it finished a buffer.
+	fn feed_audio(&mut self)
+	{
+		let (Some(device), Some(stream)) = (&mut self.audio_device, &mut self.audio_stream)
+		else
+		{
+			return;
+		};
+
+		let mut chunk = [0.0; AUDIO_CHUNK];
+
+		while device.is_audio_stream_processed(stream)
+		{
+			self.audio_ring.pop(&mut chunk);
+
+			let data: Vec<u8> = chunk
+				.iter()
+				.map(|sample| (sample.clamp(-1.0, 1.0) * 127.0 + 128.0) as u8)
+				.collect();
+			stream.update_audio_stream(&data);
+		}
+	}
+}
+
+impl Frontend for RaylibFrontend
+{
+	fn update(&mut self, system: &mut dyn System, _delta: f64)
+	{
+		for (index, binding) in self.bindings.iter().enumerate()
+		{
+			if *binding == KeyboardKey::KEY_NULL
+			{
+				continue;
+			}
+
+			if self.internals.0.is_key_down(*binding)
+			{
+				system.press_key(index as u8);
+			}
+			else
+			{
+				system.release_key(index as u8);
+			}
+		}
+
+		// Application hotkeys.
+		if self.internals.0.is_key_pressed(KeyboardKey::KEY_F5)
+		{
+			self.commands.push(FrontendCommand::DumpMemory);
+		}
+		if self.internals.0.is_key_pressed(KeyboardKey::KEY_F9)
+		{
+			self.commands.push(FrontendCommand::InjectMemory);
+		}
+	}
+
+	fn draw(&mut self, system: &mut dyn System)
+	{
+		// Only upload the texture when the screen changed, but always present it so the window stays responsive.
+		if system.get_draw_flag()
+		{
+			system.reset_draw_flag();
+			self.output.update_texture(system.frame().get_data());
+		}
+
+		let mut screen_context = self.internals.0.begin_drawing(&self.internals.1);
+
+		screen_context.draw_texture_pro(
+			&self.output,
+			Rectangle::new(0.0, 0.0, self.width as f32, self.height as f32),
+			Rectangle::new(
+				0.0,
+				0.0,
+				screen_context.get_screen_width() as f32,
+				screen_context.get_screen_height() as f32
+			),
+			Vector2::default(),
+			0.0,
+			Color::WHITE
+		);
+
+		if self.show_fps
+		{
+			screen_context.draw_fps(0, 0);
+		}
+
+		drop(screen_context);
+	}
+
+	fn play_audio(&mut self, samples: &SampleBuffer)
+	{
+		if !self.audio_enabled || samples.is_empty()
+		{
+			return;
+		}
+
+		if self.audio_device.is_none()
+		{
+			self.start_audio();
+		}
+
+		if self.resampler.get_input_rate() != samples.get_rate()
+		{
+			self.resampler = Resampler::new(samples.get_rate(), AUDIO_RATE);
+		}
+
+		let resampled = self.resampler.process_buffer(samples);
+		self.audio_ring.push(resampled.get_samples());
+
+		self.feed_audio();
+	}
+
+	#[inline]
+	fn has_quit(&self) -> bool { return self.internals.0.window_should_close(); }
+
+	#[inline]
+	fn take_commands(&mut self) -> Vec<FrontendCommand> { return std::mem::take(&mut self.commands); }
+}
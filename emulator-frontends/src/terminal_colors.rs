@@ -0,0 +1,396 @@
+use crossterm::style::{Color, SetBackgroundColor, SetForegroundColor};
+
+// How many colours the terminal can show, colours it can't are replaced by the nearest one it can.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TerminalColors
+{
+	// 24-bit colour, every colour as it is.
+	TrueColor,
+	// The xterm 256 colour palette: the 16 system colours, a 6x6x6 cube and a grey ramp.
+	Ansi256,
+	// The 16 system colours, all the Linux console and old terminals have.
+	Ansi16
+}
+
+// The xterm defaults for the 16 system colours: black, red, green, yellow, blue, magenta, cyan and
+// white, then their bright versions.
+const ANSI16_VALUES: [u32; 16] = [
+	0x000000, 0xCD0000, 0x00CD00, 0xCDCD00, 0x0000EE, 0xCD00CD, 0x00CDCD, 0xE5E5E5, 0x7F7F7F, 0xFF0000, 0x00FF00,
+	0xFFFF00, 0x5C5CFF, 0xFF00FF, 0x00FFFF, 0xFFFFFF
+];
+
+// Channel values of the 6 steps of the 256 colour cube.
+const CUBE_LEVELS: [u32; 6] = [0x00, 0x5F, 0x87, 0xAF, 0xD7, 0xFF];
+
+impl TerminalColors
+{
+	pub fn from_name(name: &str) -> Option<Self>
+	{
+		return match name
+		{
+			"truecolor" | "24bit" => Some(Self::TrueColor),
+			"256" => Some(Self::Ansi256),
+			"16" => Some(Self::Ansi16),
+			_ => None
+		};
+	}
+
+	// Goes by COLORTERM first, then by hints in TERM, then by the colour count in TERM's terminfo entry.
+	// Without any of those, 16 colours are the safe bet.
+	#[inline]
+	pub fn detect() -> Self { return Self::detect_with(|name| std::env::var(name).ok()); }
+
+	// Like detect, but reads the environment variables through variable.
+	pub fn detect_with(variable: impl Fn(&str) -> Option<String>) -> Self
+	{
+		let color_term = variable("COLORTERM").unwrap_or_default().to_lowercase();
+		if color_term.contains("truecolor") || color_term.contains("24bit")
+		{
+			return Self::TrueColor;
+		}
+
+		let term = variable("TERM").unwrap_or_default().to_lowercase();
+		if term.contains("direct") || term.contains("truecolor")
+		{
+			return Self::TrueColor;
+		}
+
+		if term.contains("256color")
+		{
+			return Self::Ansi256;
+		}
+
+		return match terminfo_colors(&term, &variable)
+		{
+			Some(colors) if colors >= 0x1000000 => Self::TrueColor,
+			Some(colors) if colors >= 256 => Self::Ansi256,
+			_ => Self::Ansi16
+		};
+	}
+
+	// The closest colour the terminal can show for a 0xRRGGBB colour, None for the 16 colour
+	// mode, which needs the sequences below.
+	pub fn convert(&self, color: u32) -> Option<Color>
+	{
+		return match self
+		{
+			Self::TrueColor => Some(Color::Rgb {
+				r: (color >> 16) as u8,
+				g: (color >> 8) as u8,
+				b: color as u8
+			}),
+			Self::Ansi256 => Some(Color::AnsiValue(nearest_ansi256(color))),
+			Self::Ansi16 => None
+		};
+	}
+
+	// The escape sequence switching the foreground to the closest colour the terminal can show.
+	pub fn foreground(&self, color: u32) -> String
+	{
+		return match self.convert(color)
+		{
+			Some(converted) => SetForegroundColor(converted).to_string(),
+			None => ansi16_sequence(color, 30, 90)
+		};
+	}
+
+	pub fn background(&self, color: u32) -> String
+	{
+		return match self.convert(color)
+		{
+			Some(converted) => SetBackgroundColor(converted).to_string(),
+			None => ansi16_sequence(color, 40, 100)
+		};
+	}
+}
+
+// SGR 30-37 and 90-97 (or 40-47 and 100-107 for the background). Crossterm writes the 16 colours
+// as 38;5;n, which the Linux console doesn't understand.
+fn ansi16_sequence(color: u32, normal: usize, bright: usize) -> String
+{
+	let index = nearest_ansi16(color);
+	let code = if index < 8 { normal + index } else { bright + index - 8 };
+
+	return format!("\x1b[{}m", code);
+}
+
+// Squared distance between two 0xRRGGBB colours.
+#[inline]
+pub fn color_distance(a: u32, b: u32) -> u32
+{
+	let channel = |shift: u32| -> u32 {
+		let difference = ((a >> shift) & 0xFF).abs_diff((b >> shift) & 0xFF);
+		return difference * difference;
+	};
+
+	return channel(16) + channel(8) + channel(0);
+}
+
+fn nearest_ansi16(color: u32) -> usize
+{
+	return (0 .. 16)
+		.min_by_key(|index| color_distance(color, ANSI16_VALUES[*index]))
+		.unwrap_or(0);
+}
+
+// The nearest entry of the colour cube or of the grey ramp, whichever is closer. The 16 system
+// colours are left out, terminals often change them.
+fn nearest_ansi256(color: u32) -> u8
+{
+	let level = |shift: u32| -> usize {
+		let value = (color >> shift) & 0xFF;
+		return (0 .. 6)
+			.min_by_key(|index| CUBE_LEVELS[*index].abs_diff(value))
+			.unwrap_or(0);
+	};
+
+	let (r, g, b) = (level(16), level(8), level(0));
+	let cube_color = CUBE_LEVELS[r] << 16 | CUBE_LEVELS[g] << 8 | CUBE_LEVELS[b];
+
+	// 24 greys from 0x08 to 0xEE in steps of 10.
+	let average = (((color >> 16) & 0xFF) + ((color >> 8) & 0xFF) + (color & 0xFF)) / 3;
+	let grey = (average.saturating_sub(3) / 10).min(23);
+	let grey_value = 8 + grey * 10;
+	let grey_color = grey_value << 16 | grey_value << 8 | grey_value;
+
+	if color_distance(color, grey_color) < color_distance(color, cube_color)
+	{
+		return 232 + grey as u8;
+	}
+
+	return 16 + (r * 36 + g * 6 + b) as u8;
+}
+
+// The "colors" number of a compiled terminfo entry, looked up where ncurses keeps them.
+fn terminfo_colors(term: &str, variable: &impl Fn(&str) -> Option<String>) -> Option<u32>
+{
+	let first = term.chars().next()?;
+
+	let mut directories = Vec::new();
+	if let Some(directory) = variable("TERMINFO")
+	{
+		directories.push(directory);
+	}
+	if let Some(home) = variable("HOME")
+	{
+		directories.push(format!("{}/.terminfo", home));
+	}
+	directories.extend(["/etc/terminfo", "/lib/terminfo", "/usr/share/terminfo"].map(String::from));
+
+	for directory in directories
+	{
+		// Linux sorts entries by their first letter, macOS by its hex code.
+		for path in [
+			format!("{}/{}/{}", directory, first, term),
+			format!("{}/{:x}/{}", directory, first as u32, term)
+		]
+		{
+			if let Ok(data) = std::fs::read(&path)
+			{
+				return parse_terminfo_colors(&data);
+			}
+		}
+	}
+
+	return None;
+}
+
+// Numbers are 16 bit in the legacy format (magic 0x011A) and 32 bit in the extended one (0x021E).
+fn parse_terminfo_colors(data: &[u8]) -> Option<u32>
+{
+	// The index of "colors" among the numeric capabilities.
+	const COLORS: usize = 13;
+
+	let short = |offset: usize| -> Option<usize> {
+		return Some(u16::from_le_bytes([*data.get(offset)?, *data.get(offset + 1)?]) as usize);
+	};
+
+	let number_size = match short(0)?
+	{
+		0x011A => 2,
+		0x021E => 4,
+		_ => return None
+	};
+
+	let (names_size, bools_count, numbers_count) = (short(2)?, short(4)?, short(6)?);
+	if COLORS >= numbers_count
+	{
+		return None;
+	}
+
+	// Numbers start on an even offset.
+	let mut offset = 12 + names_size + bools_count;
+	offset += offset % 2;
+	offset += COLORS * number_size;
+
+	let value = match number_size
+	{
+		2 => short(offset)? as u32,
+		_ => u32::from_le_bytes(data.get(offset .. offset + 4)?.try_into().ok()?)
+	};
+
+	// Missing capabilities are stored as -1.
+	return match number_size
+	{
+		2 if value >= 0x8000 => None,
+		4 if value >= 0x80000000 => None,
+		_ => Some(value)
+	};
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn pure_colours_map_to_the_cube_and_the_system_colours()
+	{
+		let cases = [
+			(0x000000, 16, 0),
+			(0xFF0000, 196, 9),
+			(0x00FF00, 46, 10),
+			(0x0000FF, 21, 4),
+			(0xFFFF00, 226, 11),
+			(0xFFFFFF, 231, 15),
+			(0xCD0000, 160, 1)
+		];
+
+		for (color, ansi256, ansi16) in cases
+		{
+			assert_eq!(nearest_ansi256(color), ansi256, "{:06X} in 256 colours", color);
+			assert_eq!(nearest_ansi16(color), ansi16, "{:06X} in 16 colours", color);
+		}
+	}
+
+	#[test]
+	fn greys_pick_the_ramp_unless_the_cube_is_closer()
+	{
+		let cases = [
+			// On the ramp, far from any cube level.
+			(0x1C1C1C, 234),
+			(0x808080, 244),
+			(0xEEEEEE, 255),
+			// Exactly a cube level.
+			(0x5F5F5F, 59),
+			(0x000000, 16),
+			(0xFFFFFF, 231),
+			// Grey-ish but tinted, the cube is closer.
+			(0x5F875F, 65)
+		];
+
+		for (color, expected) in cases
+		{
+			assert_eq!(nearest_ansi256(color), expected, "{:06X}", color);
+		}
+	}
+
+	#[test]
+	fn sixteen_colours_use_plain_sgr_codes()
+	{
+		assert_eq!(TerminalColors::Ansi16.foreground(0x000000), "\x1b[30m");
+		assert_eq!(TerminalColors::Ansi16.foreground(0xFFFFFF), "\x1b[97m");
+		assert_eq!(TerminalColors::Ansi16.background(0xCD0000), "\x1b[41m");
+		assert_eq!(TerminalColors::Ansi16.background(0xFF0000), "\x1b[101m");
+	}
+
+	// A compiled terminfo entry with the given colour count, or without one for None.
+	fn terminfo(extended: bool, colors: Option<u32>) -> Vec<u8>
+	{
+		let names = b"test|a test terminal\0";
+		let bools = [1u8, 0, 1];
+		let (magic, number_size) = if extended { (0x021Eu16, 4) } else { (0x011Au16, 2) };
+
+		let mut result = Vec::new();
+		for value in [magic, names.len() as u16, bools.len() as u16, 15, 0, 0]
+		{
+			result.extend_from_slice(&value.to_le_bytes());
+		}
+		result.extend_from_slice(names);
+		result.extend_from_slice(&bools);
+		if result.len() % 2 == 1
+		{
+			result.push(0);
+		}
+
+		for index in 0 .. 15
+		{
+			let value = match (index, colors)
+			{
+				(13, Some(colors)) => colors,
+				_ => u32::MAX
+			};
+			result.extend_from_slice(&value.to_le_bytes()[.. number_size]);
+		}
+
+		return result;
+	}
+
+	#[test]
+	fn terminfo_colour_counts_are_read()
+	{
+		assert_eq!(parse_terminfo_colors(&terminfo(false, Some(8))), Some(8));
+		assert_eq!(parse_terminfo_colors(&terminfo(false, Some(256))), Some(256));
+		assert_eq!(parse_terminfo_colors(&terminfo(true, Some(256))), Some(256));
+		assert_eq!(parse_terminfo_colors(&terminfo(true, Some(0x1000000))), Some(0x1000000));
+	}
+
+	#[test]
+	fn broken_terminfo_has_no_colour_count()
+	{
+		assert_eq!(parse_terminfo_colors(&terminfo(false, None)), None);
+		assert_eq!(parse_terminfo_colors(&terminfo(true, None)), None);
+
+		let data = terminfo(false, Some(256));
+		assert_eq!(parse_terminfo_colors(&data[.. data.len() - 4]), None);
+		assert_eq!(parse_terminfo_colors(&data[.. 5]), None);
+		assert_eq!(parse_terminfo_colors(&[0x1A, 0x02, 0, 0]), None);
+
+		// Fewer numbers than the index of "colors".
+		let mut data = terminfo(false, Some(256));
+		data[6] = 13;
+		assert_eq!(parse_terminfo_colors(&data), None);
+	}
+
+	#[test]
+	fn detect_goes_by_colorterm_then_term_then_terminfo()
+	{
+		let directory = std::env::temp_dir().join(format!("remu-terminfo-{}", std::process::id()));
+		std::fs::create_dir_all(directory.join("r")).unwrap();
+		std::fs::write(directory.join("r/remu-test-8"), terminfo(false, Some(8))).unwrap();
+		std::fs::write(directory.join("r/remu-test-256"), terminfo(false, Some(256))).unwrap();
+		std::fs::write(directory.join("r/remu-test-rgb"), terminfo(true, Some(0x1000000))).unwrap();
+
+		let terminfo_directory = directory.to_str().unwrap().to_string();
+		let detect = |color_term: &str, term: &str| -> TerminalColors {
+			return TerminalColors::detect_with(|name| match name
+			{
+				"COLORTERM" if !color_term.is_empty() => Some(color_term.to_string()),
+				"TERM" if !term.is_empty() => Some(term.to_string()),
+				"TERMINFO" => Some(terminfo_directory.clone()),
+				_ => None
+			});
+		};
+
+		let cases = [
+			("truecolor", "linux", TerminalColors::TrueColor),
+			("24bit", "", TerminalColors::TrueColor),
+			("yes", "xterm-256color", TerminalColors::Ansi256),
+			("", "xterm-direct", TerminalColors::TrueColor),
+			("", "screen-256color", TerminalColors::Ansi256),
+			("", "remu-test-8", TerminalColors::Ansi16),
+			("", "remu-test-256", TerminalColors::Ansi256),
+			("", "remu-test-rgb", TerminalColors::TrueColor),
+			("", "remu-test-missing", TerminalColors::Ansi16),
+			("", "", TerminalColors::Ansi16)
+		];
+
+		let results: Vec<TerminalColors> = cases.iter().map(|(color_term, term, _)| detect(color_term, term)).collect();
+		let _ = std::fs::remove_dir_all(&directory);
+
+		for ((color_term, term, expected), result) in cases.iter().zip(results)
+		{
+			assert_eq!(result, *expected, "COLORTERM={} TERM={}", color_term, term);
+		}
+	}
+}
@@ -0,0 +1,332 @@
+use std::io::{stdout, Write};
+use std::time::Duration;
+
+use crossterm::{cursor, event::*, execute, queue, style::*, terminal::*};
+use serde_json::Value;
+
+use emulator_common::{key_names_from_json, Framebuffer, Frontend, FrontendCommand, System};
+
+use crate::{
+	encode_kitty, encode_sixel, graphics_scale, probe_terminal, TerminalColors, TerminalGraphics, TerminalRenderer,
+	KITTY_DELETE
+};
+
+// Cell size in pixels when the terminal doesn't report its own.
+const DEFAULT_CELL_SIZE: (usize, usize) = (8, 16);
+
+pub struct TerminalFrontendConfig
+{
+	pub bindings: Vec<(KeyCode, KeyModifiers)>,
+	pub renderer: TerminalRenderer,
+	// None asks the terminal what it supports.
+	pub graphics: Option<TerminalGraphics>,
+	// None detects them from the environment.
+	pub colors: Option<TerminalColors>,
+	// Rings the terminal bell when the buzzer starts, the only sound a terminal has.
+	pub bell: bool
+}
+
+impl TerminalFrontendConfig
+{
+	pub fn default() -> Self
+	{
+		Self {
+			bindings: Vec::new(),
+			renderer: TerminalRenderer::HalfBlocks,
+			graphics: None,
+			colors: None,
+			bell: false
+		}
+	}
+
+	pub fn from_json(data: &Value, key_count: usize) -> Self
+	{
+		let mut result = Self::default();
+
+		if let Some(name) = data["renderer"].as_str()
+		{
+			match TerminalRenderer::from_name(name)
+			{
+				Some(renderer) => result.renderer = renderer,
+				None => warn!("Unknown terminal renderer: {}, keeping {:?}.", name, result.renderer),
+			}
+		}
+
+		match data["graphics"].as_str()
+		{
+			None | Some("auto") =>
+			{},
+			Some(name) => match TerminalGraphics::from_name(name)
+			{
+				Some(graphics) => result.graphics = Some(graphics),
+				None => warn!("Unknown terminal graphics: {}, detecting them instead.", name),
+			},
+		}
+
+		match data["colors"].as_str()
+		{
+			None | Some("auto") =>
+			{},
+			Some(name) => match TerminalColors::from_name(name)
+			{
+				Some(colors) => result.colors = Some(colors),
+				None => warn!("Unknown terminal colours: {}, detecting them instead.", name),
+			},
+		}
+
+		result.bell = data["bell"].as_bool().unwrap_or(result.bell);
+
+		// Set the bindings, "keys_modifiers" is optional and lines up with "keys".
+		let modifiers = data["keys_modifiers"].as_array();
+
+		for (index, key) in key_names_from_json(data, key_count).iter().enumerate()
+		{
+			let modifier = modifiers
+				.and_then(|modifiers| modifiers.get(index))
+				.and_then(|modifier| modifier.as_str())
+				.unwrap_or("NONE");
+
+			result.bindings.push((
+				KeyCode::Char(key.chars().next().unwrap_or('\0')),
+				match modifier
+				{
+					"NONE" => KeyModifiers::NONE,
+					"SHIFT" => KeyModifiers::SHIFT,
+					"CONTROL" => KeyModifiers::CONTROL,
+					"ALT" => KeyModifiers::ALT,
+					_ => KeyModifiers::NONE
+				}
+			));
+		}
+
+		info!("Terminal frontend config loaded successfully from JSON data.");
+
+		return result;
+	}
+}
+
+pub struct TerminalFrontend
+{
+	quit: bool,
+	bindings: Vec<(KeyCode, KeyModifiers)>,
+	renderer: TerminalRenderer,
+	graphics: TerminalGraphics,
+	colors: TerminalColors,
+	// Pixels per cell, to size bitmaps.
+	cell_size: (usize, usize),
+	// Terminal size at the last draw, a resize leaves old output behind that has to be cleared.
+	terminal_size: (u16, u16),
+	bell: bool,
+	// Whether the buzzer was on at the last update, the bell only rings when it turns on.
+	sound_playing: bool,
+	commands: Vec<FrontendCommand>
+}
+
+impl TerminalFrontend
+{
+	pub fn new(config: &TerminalFrontendConfig) -> Self
+	{
+		execute!(
+			stdout(),
+			Clear(ClearType::All),
+			cursor::Hide,
+			PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES),
+			SetTitle("remu")
+		)
+		.unwrap();
+
+		enable_raw_mode().unwrap();
+
+		let terminal_size = size().unwrap_or((80, 24));
+		let mut graphics = TerminalGraphics::Text;
+		let mut cell_size = DEFAULT_CELL_SIZE;
+
+		if config.graphics != Some(TerminalGraphics::Text)
+		{
+			let probe = probe_terminal(Duration::from_millis(500));
+			graphics = config.graphics.unwrap_or(probe.best_graphics());
+
+			if let Some((width, height)) = probe.text_area
+			{
+				cell_size = (
+					(width / terminal_size.0.max(1) as usize).max(1),
+					(height / terminal_size.1.max(1) as usize).max(1)
+				);
+			}
+		}
+
+		let colors = config.colors.unwrap_or_else(TerminalColors::detect);
+
+		info!(
+			"Terminal frontend initialized successfully, drawing with {:?} in {:?}.",
+			graphics, colors
+		);
+
+		Self {
+			quit: false,
+			bindings: config.bindings.clone(),
+			renderer: config.renderer,
+			graphics,
+			colors,
+			cell_size,
+			terminal_size,
+			bell: config.bell,
+			sound_playing: false,
+			commands: Vec::new()
+		}
+	}
+
+	fn draw_text(&self, frame: &Framebuffer, terminal_size: (u16, u16))
+	{
+		// Shrink the screen until it fits the terminal.
+		let factor = self.renderer.fit_factor(
+			frame.get_width(),
+			frame.get_height(),
+			terminal_size.0 as usize,
+			terminal_size.1 as usize
+		);
+		let lines = match factor
+		{
+			1 => self.renderer.render(frame, self.colors),
+			factor => self.renderer.render(&frame.downscaled(factor), self.colors)
+		};
+
+		for line in lines
+		{
+			queue!(stdout(), Print(line), ResetColor, cursor::MoveToNextLine(1)).unwrap();
+		}
+	}
+
+	fn draw_bitmap(&self, frame: &Framebuffer, terminal_size: (u16, u16))
+	{
+		// One row stays free, an image reaching the last row would scroll the terminal.
+		let cells = (terminal_size.0 as usize, (terminal_size.1 as usize).saturating_sub(1).max(1));
+		let scale = graphics_scale(frame.get_width(), frame.get_height(), cells, self.cell_size);
+
+		let image = match self.graphics
+		{
+			// Kitty scales the image itself, it only needs to know how many cells to cover.
+			TerminalGraphics::Kitty => encode_kitty(
+				frame,
+				(frame.get_width() as f64 * scale / self.cell_size.0 as f64) as usize,
+				(frame.get_height() as f64 * scale / self.cell_size.1 as f64) as usize
+			),
+			_ if scale >= 1.0 => encode_sixel(frame, scale as usize),
+			_ => encode_sixel(&frame.downscaled((1.0 / scale).ceil() as usize), 1)
+		};
+
+		queue!(stdout(), Print(image)).unwrap();
+	}
+
+	fn exit(&mut self)
+	{
+		self.quit = true;
+
+		disable_raw_mode().unwrap();
+
+		if self.graphics == TerminalGraphics::Kitty
+		{
+			execute!(stdout(), Print(KITTY_DELETE)).unwrap();
+		}
+
+		execute!(stdout(), cursor::Show, PopKeyboardEnhancementFlags,).unwrap();
+	}
+}
+
+impl Frontend for TerminalFrontend
+{
+	fn update(&mut self, system: &mut dyn System, _delta: f64)
+	{
+		let sound_playing = system.is_sound_playing();
+		if self.bell && sound_playing && !self.sound_playing
+		{
+			print!("{}", '\x07');
+		}
+		self.sound_playing = sound_playing;
+
+		if poll(Duration::from_millis(1)).unwrap()
+		{
+			// Read the input.
+			match read().unwrap()
+			{
+				Event::Key(event) =>
+				{
+					// CTRL + C / CTRL + Z / Escape to close the app.
+					if (event.modifiers == KeyModifiers::CONTROL
+						&& (event.code == KeyCode::Char('c') || event.code == KeyCode::Char('z')))
+						|| event.code == KeyCode::Esc
+					{
+						info!("Closing on user request...");
+						self.exit();
+						return;
+					}
+
+					// Application hotkeys.
+					if event.kind == KeyEventKind::Press
+					{
+						match event.code
+						{
+							KeyCode::F(5) => self.commands.push(FrontendCommand::DumpMemory),
+							KeyCode::F(9) => self.commands.push(FrontendCommand::InjectMemory),
+							_ =>
+							{}
+						}
+					}
+
+					for (index, binding) in self.bindings.iter().enumerate()
+					{
+						if (event.code, event.modifiers) == *binding
+						{
+							if event.kind == KeyEventKind::Press
+							{
+								system.press_key(index as u8);
+							}
+							else
+							{
+								system.release_key(index as u8)
+							}
+						}
+					}
+				},
+				_ =>
+				{}
+			}
+		}
+	}
+
+	fn draw(&mut self, system: &mut dyn System)
+	{
+		if !system.get_draw_flag() || self.quit
+		{
+			return;
+		}
+
+		system.reset_draw_flag();
+
+		let terminal_size = size().unwrap_or(self.terminal_size);
+		if terminal_size != self.terminal_size
+		{
+			self.terminal_size = terminal_size;
+			queue!(stdout(), Clear(ClearType::All)).unwrap();
+		}
+
+		queue!(stdout(), cursor::MoveTo(0, 0)).unwrap();
+
+		let frame = system.frame();
+		match self.graphics
+		{
+			TerminalGraphics::Text => self.draw_text(frame, terminal_size),
+			_ => self.draw_bitmap(frame, terminal_size)
+		}
+
+		queue!(stdout(), ResetColor).unwrap();
+
+		stdout().flush().unwrap();
+	}
+
+	#[inline]
+	fn has_quit(&self) -> bool { return self.quit; }
+
+	#[inline]
+	fn take_commands(&mut self) -> Vec<FrontendCommand> { return std::mem::take(&mut self.commands); }
+}
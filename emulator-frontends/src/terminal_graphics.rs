@@ -0,0 +1,455 @@
+use std::collections::HashMap;
+use std::fmt::Write as FmtWrite;
+use std::io::{stdout, Write};
+use std::time::{Duration, Instant};
+
+use emulator_common::{encode_base64, Framebuffer};
+
+// How the terminal frontend gets the screen onto the terminal.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TerminalGraphics
+{
+	// Characters picked by the TerminalRenderer, works everywhere.
+	Text,
+	// DEC sixel bitmaps, supported by xterm, foot, mlterm, WezTerm and others.
+	Sixel,
+	// The kitty graphics protocol, supported by kitty, WezTerm, Konsole and Ghostty.
+	Kitty
+}
+
+impl TerminalGraphics
+{
+	pub fn from_name(name: &str) -> Option<Self>
+	{
+		return match name
+		{
+			"text" => Some(Self::Text),
+			"sixel" => Some(Self::Sixel),
+			"kitty" => Some(Self::Kitty),
+			_ => None
+		};
+	}
+}
+
+// What the terminal answered when asked about graphics.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TerminalProbe
+{
+	pub kitty: bool,
+	pub sixel: bool,
+	// Size of the text area in pixels, if the terminal reports it.
+	pub text_area: Option<(usize, usize)>
+}
+
+impl TerminalProbe
+{
+	// Kitty first, it takes full colour images and scales them itself.
+	pub fn best_graphics(&self) -> TerminalGraphics
+	{
+		if self.kitty
+		{
+			return TerminalGraphics::Kitty;
+		}
+
+		if self.sixel
+		{
+			return TerminalGraphics::Sixel;
+		}
+
+		return TerminalGraphics::Text;
+	}
+
+	// Picks the answers out of everything the terminal sent back.
+	pub fn parse(response: &[u8]) -> Self
+	{
+		let text = String::from_utf8_lossy(response);
+
+		// Primary device attributes, ESC [ ? 62 ; 4 ; ... c. Attribute 4 means sixel. Every answer
+		// only counts once its final character is in, a cut off one could be anything.
+		let sixel = text
+			.split("\x1b[?")
+			.nth(1)
+			.and_then(|attributes| attributes.split_once('c'))
+			.is_some_and(|(attributes, _)| attributes.split(';').any(|attribute| attribute == "4"));
+
+		// Text area size, ESC [ 4 ; height ; width t.
+		let text_area = text
+			.split("\x1b[4;")
+			.nth(1)
+			.and_then(|size| size.split_once('t'))
+			.and_then(|(size, _)| {
+				let mut values = size.split(';').map(|value| value.parse::<usize>().ok());
+				return match (values.next().flatten(), values.next().flatten())
+				{
+					(Some(height), Some(width)) if width > 0 && height > 0 => Some((width, height)),
+					_ => None
+				};
+			});
+
+		return Self {
+			kitty: text.contains("\x1b_Gi=31;OK\x1b\\"),
+			sixel,
+			text_area
+		};
+	}
+}
+
+// Asks the terminal about kitty graphics, its size in pixels and its device attributes. Raw mode
+// has to be on. Every terminal answers the device attributes, and it is asked last, so its answer
+// marks the end of the response. Terminals that stay quiet count as text only after the timeout.
+pub fn probe_terminal(timeout: Duration) -> TerminalProbe
+{
+	let mut output = stdout();
+	let _ = write!(output, "\x1b_Gi=31,s=1,v=1,a=q,t=d,f=24;AAAA\x1b\\\x1b[14t\x1b[c");
+	let _ = output.flush();
+
+	let deadline = Instant::now() + timeout;
+	let mut response = Vec::new();
+
+	while let Some(byte) = read_byte(deadline)
+	{
+		response.push(byte);
+
+		let text = String::from_utf8_lossy(&response);
+		if text
+			.split("\x1b[?")
+			.nth(1)
+			.is_some_and(|attributes| attributes.contains('c'))
+		{
+			return TerminalProbe::parse(&response);
+		}
+	}
+
+	warn!("The terminal did not answer the graphics query, using text.");
+
+	return TerminalProbe {
+		kitty: false,
+		sixel: false,
+		text_area: None
+	};
+}
+
+// One byte from stdin if it arrives before the deadline. It reads a byte at a time straight from
+// the descriptor, so nothing past the answer is taken away from the frontend's key input.
+#[cfg(unix)]
+fn read_byte(deadline: Instant) -> Option<u8>
+{
+	let remaining = deadline.saturating_duration_since(Instant::now());
+	let mut descriptor = libc::pollfd {
+		fd: libc::STDIN_FILENO,
+		events: libc::POLLIN,
+		revents: 0
+	};
+
+	// Round up, so the last fraction of a millisecond still waits instead of spinning.
+	let milliseconds = remaining.as_micros().div_ceil(1000).min(i32::MAX as u128) as i32;
+	if milliseconds == 0 || unsafe { libc::poll(&mut descriptor, 1, milliseconds) } != 1
+	{
+		return None;
+	}
+
+	let mut byte = 0u8;
+	let count = unsafe { libc::read(libc::STDIN_FILENO, &mut byte as *mut u8 as *mut libc::c_void, 1) };
+
+	return if count == 1 { Some(byte) } else { None };
+}
+
+// Without a way to wait on stdin with a timeout the terminal isn't asked, it gets text.
+#[cfg(not(unix))]
+fn read_byte(_deadline: Instant) -> Option<u8> { return None; }
+
+// How many terminal pixels one frame pixel can take while the frame still fits into the given cells.
+pub fn graphics_scale(width: usize, height: usize, cells: (usize, usize), cell_size: (usize, usize)) -> f64
+{
+	let horizontal = (cells.0 * cell_size.0) as f64 / width.max(1) as f64;
+	let vertical = (cells.1 * cell_size.1) as f64 / height.max(1) as f64;
+
+	return horizontal.min(vertical);
+}
+
+// The frame as a kitty image stretched over columns x rows cells at the cursor. Sending it again
+// replaces the last one instead of stacking another image on top.
+pub fn encode_kitty(frame: &Framebuffer, columns: usize, rows: usize) -> String
+{
+	let data = encode_base64(frame.get_data());
+	let chunks: Vec<&[u8]> = data.as_bytes().chunks(4096).collect();
+
+	let mut result = String::with_capacity(data.len() + chunks.len() * 16 + 64);
+
+	for (index, chunk) in chunks.iter().enumerate()
+	{
+		let more = (index + 1 < chunks.len()) as u8;
+		let chunk = std::str::from_utf8(chunk).unwrap_or("");
+
+		if index == 0
+		{
+			let _ = write!(
+				result,
+				"\x1b_Ga=T,f=32,s={},v={},i=1,p=1,q=2,C=1,c={},r={},m={};{}\x1b\\",
+				frame.get_width(),
+				frame.get_height(),
+				columns.max(1),
+				rows.max(1),
+				more,
+				chunk
+			);
+		}
+		else
+		{
+			let _ = write!(result, "\x1b_Gm={};{}\x1b\\", more, chunk);
+		}
+	}
+
+	return result;
+}
+
+// Removes the image encode_kitty placed.
+pub const KITTY_DELETE: &str = "\x1b_Ga=d,d=I,i=1,q=2\x1b\\";
+
+// The frame as a sixel image, every pixel scale x scale terminal pixels. Sixel has at most 256
+// colour registers, frames with more colours than that are reduced to a 6x6x6 colour cube.
+pub fn encode_sixel(frame: &Framebuffer, scale: usize) -> String
+{
+	let scale = scale.max(1);
+	let width = frame.get_width() * scale;
+	let height = frame.get_height() * scale;
+
+	let mut colors: Vec<u32> = Vec::new();
+	let mut registers: HashMap<u32, usize> = HashMap::new();
+	let mut indices = vec![0; frame.get_width() * frame.get_height()];
+
+	for y in 0 .. frame.get_height()
+	{
+		for x in 0 .. frame.get_width()
+		{
+			let color = frame.get_pixel(x, y);
+			let register = *registers.entry(color).or_insert_with(|| {
+				colors.push(color);
+				return colors.len() - 1;
+			});
+			indices[y * frame.get_width() + x] = register;
+		}
+	}
+
+	if colors.len() > 256
+	{
+		let cube = |color: u32| -> usize {
+			let level = |shift: u32| -> usize { return (((color >> shift) & 0xFF) as usize * 5 + 127) / 255; };
+			return level(16) * 36 + level(8) * 6 + level(0);
+		};
+
+		colors = (0 .. 216)
+			.map(|index: u32| (index / 36 * 0x33) << 16 | (index / 6 % 6 * 0x33) << 8 | (index % 6 * 0x33))
+			.collect();
+
+		for y in 0 .. frame.get_height()
+		{
+			for x in 0 .. frame.get_width()
+			{
+				indices[y * frame.get_width() + x] = cube(frame.get_pixel(x, y));
+			}
+		}
+	}
+
+	let mut result = format!("\x1bPq\"1;1;{};{}", width, height);
+
+	for (register, color) in colors.iter().enumerate()
+	{
+		let percent = |shift: u32| -> u32 { return ((color >> shift) & 0xFF) * 100 / 255; };
+		let _ = write!(result, "#{};2;{};{};{}", register, percent(16), percent(8), percent(0));
+	}
+
+	// Every band is 6 pixels high, one row of sixels per colour used in it.
+	let mut sixels = vec![vec![0u8; width]; colors.len()];
+	let mut used = vec![false; colors.len()];
+
+	for band in (0 .. height).step_by(6)
+	{
+		for row in sixels.iter_mut()
+		{
+			row.fill(0);
+		}
+		used.fill(false);
+
+		for bit in 0 .. 6.min(height - band)
+		{
+			let y = (band + bit) / scale;
+			for x in 0 .. width
+			{
+				let register = indices[y * frame.get_width() + x / scale];
+				sixels[register][x] |= 1 << bit;
+				used[register] = true;
+			}
+		}
+
+		for register in (0 .. colors.len()).filter(|register| used[*register])
+		{
+			let _ = write!(result, "#{}", register);
+			push_sixel_row(&mut result, &sixels[register]);
+			result.push('$');
+		}
+
+		result.push('-');
+	}
+
+	result.push_str("\x1b\\");
+
+	return result;
+}
+
+// Run-length encodes a row of sixels, runs of 4 and more as !count.
+fn push_sixel_row(output: &mut String, row: &[u8])
+{
+	let mut index = 0;
+
+	while index < row.len()
+	{
+		let value = row[index];
+		let mut count = 1;
+		while index + count < row.len() && row[index + count] == value
+		{
+			count += 1;
+		}
+
+		let symbol = (63 + value) as char;
+		if count >= 4
+		{
+			let _ = write!(output, "!{}{}", count, symbol);
+		}
+		else
+		{
+			for _ in 0 .. count
+			{
+				output.push(symbol);
+			}
+		}
+
+		index += count;
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	const DA1_SIXEL: &str = "\x1b[?62;4;6;22c";
+	const DA1_PLAIN: &str = "\x1b[?62;22c";
+	const KITTY_OK: &str = "\x1b_Gi=31;OK\x1b\\";
+	const TEXT_AREA: &str = "\x1b[4;768;1024t";
+
+	fn parse(parts: &[&str]) -> TerminalProbe { return TerminalProbe::parse(parts.concat().as_bytes()); }
+
+	#[test]
+	fn device_attributes_tell_sixel_apart()
+	{
+		let probe = parse(&[DA1_SIXEL]);
+		assert!(probe.sixel && !probe.kitty);
+		assert_eq!(probe.best_graphics(), TerminalGraphics::Sixel);
+
+		assert!(!parse(&[DA1_PLAIN]).sixel);
+		// 42 and 14 are not 4.
+		assert!(!parse(&["\x1b[?62;42;14c"]).sixel);
+		assert!(parse(&["\x1b[?4c"]).sixel);
+	}
+
+	#[test]
+	fn kitty_wins_over_sixel()
+	{
+		let probe = parse(&[KITTY_OK, TEXT_AREA, DA1_SIXEL]);
+
+		assert!(probe.kitty && probe.sixel);
+		assert_eq!(probe.text_area, Some((1024, 768)));
+		assert_eq!(probe.best_graphics(), TerminalGraphics::Kitty);
+	}
+
+	#[test]
+	fn kitty_errors_and_silence_fall_back_to_text()
+	{
+		let probe = parse(&["\x1b_Gi=31;ENOTSUPPORTED:no graphics\x1b\\", DA1_PLAIN]);
+		assert!(!probe.kitty);
+		assert_eq!(probe.best_graphics(), TerminalGraphics::Text);
+
+		let probe = parse(&[]);
+		assert_eq!(probe.best_graphics(), TerminalGraphics::Text);
+		assert_eq!(probe.text_area, None);
+	}
+
+	#[test]
+	fn truncated_answers_do_not_count()
+	{
+		let probe = parse(&["\x1b_Gi=31;OK", "\x1b[4;768;10", "\x1b[?62;4"]);
+
+		assert!(!probe.kitty && !probe.sixel);
+		assert_eq!(probe.text_area, None);
+		assert_eq!(probe.best_graphics(), TerminalGraphics::Text);
+
+		assert_eq!(parse(&["\x1b[4;0;1024t", DA1_PLAIN]).text_area, None);
+		assert_eq!(parse(&["\x1b[4;;1024t", DA1_PLAIN]).text_area, None);
+	}
+
+	#[test]
+	fn keys_typed_in_between_are_skipped()
+	{
+		let probe = parse(&["q", KITTY_OK, "w", TEXT_AREA, "\x1b[A", DA1_SIXEL]);
+
+		assert!(probe.kitty && probe.sixel);
+		assert_eq!(probe.text_area, Some((1024, 768)));
+	}
+
+	#[test]
+	fn scale_fits_the_tighter_side()
+	{
+		// 80x24 cells of 8x16 pixels are 640x384.
+		assert_eq!(graphics_scale(64, 32, (80, 24), (8, 16)), 10.0);
+		assert_eq!(graphics_scale(256, 256, (80, 24), (8, 16)), 1.5);
+		assert_eq!(graphics_scale(0, 0, (80, 24), (8, 16)), 384.0);
+	}
+
+	#[test]
+	fn sixel_rows_are_run_length_encoded()
+	{
+		let mut row = String::new();
+		push_sixel_row(&mut row, &[1, 1, 1, 2, 2, 2, 2, 2, 0]);
+
+		// Runs below 4 are written out, longer ones as !count.
+		assert_eq!(row, "@@@!5A?");
+	}
+
+	#[test]
+	fn sixel_image_of_a_tiny_frame()
+	{
+		// Black with one white pixel in the top right corner.
+		let mut frame = Framebuffer::new(5, 2);
+		frame.fill(0x000000);
+		frame.set_pixel(4, 0, 0xFFFFFF);
+
+		assert_eq!(
+			encode_sixel(&frame, 1),
+			"\x1bPq\"1;1;5;2#0;2;0;0;0#1;2;100;100;100#0!4BA$#1!4?@$-\x1b\\"
+		);
+	}
+
+	#[test]
+	fn sixel_scale_repeats_pixels()
+	{
+		let mut frame = Framebuffer::new(1, 1);
+		frame.fill(0xFFFFFF);
+
+		assert_eq!(encode_sixel(&frame, 2), "\x1bPq\"1;1;2;2#0;2;100;100;100#0BB$-\x1b\\");
+	}
+
+	#[test]
+	fn kitty_image_is_sent_in_chunks()
+	{
+		let mut frame = Framebuffer::new(64, 32);
+		frame.fill(0x123456);
+
+		// 8 KiB of pixels are 10924 base64 characters, three chunks of at most 4096.
+		let image = encode_kitty(&frame, 40, 10);
+		assert!(image.starts_with("\x1b_Ga=T,f=32,s=64,v=32,i=1,p=1,q=2,C=1,c=40,r=10,m=1;"));
+		assert_eq!(image.matches("\x1b_Gm=1;").count(), 1);
+		assert_eq!(image.matches("\x1b_Gm=0;").count(), 1);
+		assert!(image.ends_with("\x1b\\"));
+	}
+}
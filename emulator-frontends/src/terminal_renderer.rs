@@ -0,0 +1,241 @@
+use emulator_common::Framebuffer;
+
+use crate::{color_distance, TerminalColors};
+
+// How the terminal frontend turns pixels into characters.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TerminalRenderer
+{
+	// One full block per pixel, pixels come out about twice as tall as they are wide.
+	Blocks,
+	// Two pixels stacked in every cell, square pixels in half the rows. The default.
+	HalfBlocks,
+	// Braille patterns, 2x4 pixels in every cell. Each cell only has two colours, pixels of any
+	// other colour go to whichever of them is closer.
+	Braille
+}
+
+impl TerminalRenderer
+{
+	pub fn from_name(name: &str) -> Option<Self>
+	{
+		return match name
+		{
+			"blocks" => Some(Self::Blocks),
+			"half_blocks" => Some(Self::HalfBlocks),
+			"braille" => Some(Self::Braille),
+			_ => None
+		};
+	}
+
+	// Pixels a single cell shows, horizontally and vertically.
+	#[inline]
+	pub fn get_cell_size(&self) -> (usize, usize)
+	{
+		return match self
+		{
+			Self::Blocks => (1, 1),
+			Self::HalfBlocks => (1, 2),
+			Self::Braille => (2, 4)
+		};
+	}
+
+	// The smallest factor the frame has to shrink by to fit into the given ammount of cells.
+	pub fn fit_factor(&self, width: usize, height: usize, columns: usize, rows: usize) -> usize
+	{
+		let (cell_width, cell_height) = self.get_cell_size();
+
+		let horizontal = width.div_ceil((columns * cell_width).max(1));
+		let vertical = height.div_ceil((rows * cell_height).max(1));
+
+		return horizontal.max(vertical).max(1);
+	}
+
+	// One string per terminal row, colours included as far as the terminal can show them.
+	pub fn render(&self, frame: &Framebuffer, colors: TerminalColors) -> Vec<String>
+	{
+		return match self
+		{
+			Self::Blocks => render_blocks(frame, colors),
+			Self::HalfBlocks => render_half_blocks(frame, colors),
+			Self::Braille => render_braille(frame, colors)
+		};
+	}
+}
+
+// Builds a line of cells, only emitting colour changes where the colours actually change.
+struct CellLine
+{
+	text: String,
+	colors: TerminalColors,
+	foreground: Option<u32>,
+	background: Option<u32>
+}
+
+impl CellLine
+{
+	fn new(colors: TerminalColors) -> Self
+	{
+		Self {
+			text: String::new(),
+			colors,
+			foreground: None,
+			background: None
+		}
+	}
+
+	fn push(&mut self, symbol: char, foreground: Option<u32>, background: Option<u32>)
+	{
+		if let Some(color) = foreground
+		{
+			if self.foreground != foreground
+			{
+				self.text.push_str(&self.colors.foreground(color));
+				self.foreground = foreground;
+			}
+		}
+
+		if let Some(color) = background
+		{
+			if self.background != background
+			{
+				self.text.push_str(&self.colors.background(color));
+				self.background = background;
+			}
+		}
+
+		self.text.push(symbol);
+	}
+
+	// A cell showing two pixels on top of each other. Of the characters that can draw them it
+	// picks the one needing the fewest colour changes, which keeps flat areas cheap to print.
+	fn push_halves(&mut self, top: u32, bottom: u32)
+	{
+		if top == bottom
+		{
+			if self.foreground == Some(top) && self.background != Some(top)
+			{
+				self.push('█', Some(top), None);
+			}
+			else
+			{
+				self.push(' ', None, Some(top));
+			}
+			return;
+		}
+
+		let changes = |foreground: u32, background: u32| -> usize {
+			return (self.foreground != Some(foreground)) as usize + (self.background != Some(background)) as usize;
+		};
+
+		if changes(bottom, top) < changes(top, bottom)
+		{
+			self.push('▄', Some(bottom), Some(top));
+		}
+		else
+		{
+			self.push('▀', Some(top), Some(bottom));
+		}
+	}
+}
+
+fn render_blocks(frame: &Framebuffer, colors: TerminalColors) -> Vec<String>
+{
+	let mut result = Vec::with_capacity(frame.get_height());
+
+	for y in 0 .. frame.get_height()
+	{
+		let mut line = CellLine::new(colors);
+
+		for x in 0 .. frame.get_width()
+		{
+			line.push('█', Some(frame.get_pixel(x, y)), None);
+		}
+
+		result.push(line.text);
+	}
+
+	return result;
+}
+
+fn render_half_blocks(frame: &Framebuffer, colors: TerminalColors) -> Vec<String>
+{
+	let mut result = Vec::with_capacity(frame.get_height().div_ceil(2));
+
+	for y in (0 .. frame.get_height()).step_by(2)
+	{
+		let mut line = CellLine::new(colors);
+
+		for x in 0 .. frame.get_width()
+		{
+			// An odd height leaves the last row without a bottom half.
+			let bottom = if y + 1 < frame.get_height() { frame.get_pixel(x, y + 1) } else { 0x000000 };
+
+			line.push_halves(frame.get_pixel(x, y), bottom);
+		}
+
+		result.push(line.text);
+	}
+
+	return result;
+}
+
+// The bit of every dot in a Braille pattern, by row and column.
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+fn render_braille(frame: &Framebuffer, colors: TerminalColors) -> Vec<String>
+{
+	let mut result = Vec::with_capacity(frame.get_height().div_ceil(4));
+
+	for cell_y in (0 .. frame.get_height()).step_by(4)
+	{
+		let mut line = CellLine::new(colors);
+
+		for cell_x in (0 .. frame.get_width()).step_by(2)
+		{
+			// Pixels past the right or bottom edge are left out.
+			let mut pixels: Vec<(u32, u32)> = Vec::with_capacity(8);
+			for (row, bits) in BRAILLE_DOTS.iter().enumerate()
+			{
+				for (column, bit) in bits.iter().enumerate()
+				{
+					if cell_x + column < frame.get_width() && cell_y + row < frame.get_height()
+					{
+						pixels.push((*bit, frame.get_pixel(cell_x + column, cell_y + row)));
+					}
+				}
+			}
+
+			// The most common colour is the background, the most common of the rest the dots.
+			let mut counts: Vec<(u32, usize)> = Vec::new();
+			for (_, color) in &pixels
+			{
+				match counts.iter_mut().find(|(counted, _)| counted == color)
+				{
+					Some((_, count)) => *count += 1,
+					None => counts.push((*color, 1)),
+				}
+			}
+			counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+			let background = counts[0].0;
+			if counts.len() == 1
+			{
+				line.push(' ', None, Some(background));
+				continue;
+			}
+			let foreground = counts[1].0;
+
+			let pattern = pixels
+				.iter()
+				.filter(|(_, color)| color_distance(*color, foreground) < color_distance(*color, background))
+				.fold(0, |pattern, (bit, _)| pattern | bit);
+
+			line.push(char::from_u32(0x2800 + pattern).unwrap_or(' '), Some(foreground), Some(background));
+		}
+
+		result.push(line.text);
+	}
+
+	return result;
+}